@@ -0,0 +1,267 @@
+// src/address.rs
+use std::fmt;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l"; // Base32 alphabet of Kaspa addresses
+const CHECKSUM_LEN: usize = 8; // 40-bit checksum, eight base32 characters
+const GENERATORS: [u64; 5] = [0x98f2bc8e61, 0x79b76d99e2, 0xf33e5fb3c4, 0xae2eabe2a8, 0x1e4f43e470];
+
+// Address prefixes and the networks they belong to
+const PREFIXES: [(&str, &str); 4] = [("kaspa", "mainnet"), ("kaspatest", "testnet"), ("kaspasim", "simnet"), ("kaspadev", "devnet")];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    Empty,
+    UnknownPrefix(String),
+    WrongNetwork { found: String, expected: String }, // Valid address, but for another network
+    InvalidCharacter(char),
+    Length(usize),                                    // Characters after the prefix
+    Checksum,
+    Padding,                                          // Unused bits at the end of the payload are not zero
+    Version(u8),
+    PayloadLength { version: u8, length: usize },
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::Empty => write!(f, "the address is empty"),
+            AddressError::UnknownPrefix(prefix) => write!(f, "`{}:` is not a Kaspa address prefix, addresses start with `kaspa:` or `kaspatest:`", prefix),
+            AddressError::WrongNetwork { found, expected } => write!(
+                f,
+                "it is a {} address (`{}:`) and this network only has {} addresses, which start with `{}:`",
+                network_of(found), found, network_of(expected), expected,
+            ),
+            AddressError::InvalidCharacter(c) => write!(f, "`{}` cannot appear in a Kaspa address", c),
+            AddressError::Length(length) => write!(f, "the address is {} characters long after the prefix, a Kaspa address has 61 or 63", length),
+            AddressError::Checksum => write!(f, "the checksum does not match, a character is probably mistyped or missing"),
+            AddressError::Padding => write!(f, "the address has padding bits set, a character is probably mistyped"),
+            AddressError::Version(version) => write!(f, "unknown address version {}", version),
+            AddressError::PayloadLength { version, length } => write!(f, "a version {} address holds {} bytes, not {}", version, expected_length(*version).unwrap_or(0), length),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+// A checked Kaspa address in its normal form: lowercase with the prefix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    prefix: String,
+    body: String,
+    pub version: u8,
+}
+
+impl Address {
+    // Parse what a user typed for a network whose addresses use `expected_prefix`.
+    // Case is ignored and a missing prefix is taken to be the network's one.
+    pub fn parse(input: &str, expected_prefix: &str) -> Result<Self, AddressError> {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return Err(AddressError::Empty);
+        }
+
+        let (prefix, body) = match input.split_once(':') {
+            Some((prefix, body)) => (prefix.to_string(), body.to_string()),
+            None => {
+                // Without a prefix the checksum tells which network the address was made for
+                let guessed = PREFIXES.iter()
+                    .map(|(prefix, _)| *prefix)
+                    .find(|prefix| *prefix != expected_prefix && checksum_ok(prefix, &input).unwrap_or(false));
+                (guessed.unwrap_or(expected_prefix).to_string(), input)
+            },
+        };
+        if !PREFIXES.iter().any(|(known, _)| *known == prefix) {
+            return Err(AddressError::UnknownPrefix(prefix));
+        }
+
+        let values = decode_body(&body)?;
+        if body.len() != 61 && body.len() != 63 {
+            return Err(AddressError::Length(body.len()));
+        }
+        if polymod(&prefix, &values) != 0 {
+            return Err(AddressError::Checksum);
+        }
+
+        let data = from_base32(&values[..values.len() - CHECKSUM_LEN]).ok_or(AddressError::Padding)?;
+        let (version, payload) = data.split_first().ok_or(AddressError::Length(body.len()))?;
+        match expected_length(*version) {
+            None => return Err(AddressError::Version(*version)),
+            Some(length) if length != payload.len() => return Err(AddressError::PayloadLength { version: *version, length: payload.len() }),
+            Some(_) => {},
+        }
+
+        // Checked last so the user only hears about the network once the address itself is fine
+        if prefix != expected_prefix {
+            return Err(AddressError::WrongNetwork { found: prefix, expected: expected_prefix.to_string() });
+        }
+        Ok(Address { prefix, body, version: *version })
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.prefix, self.body)
+    }
+}
+
+// Payload bytes of each address version: 0 Schnorr public key, 1 ECDSA public key, 8 script hash
+fn expected_length(version: u8) -> Option<usize> {
+    match version {
+        0 | 8 => Some(32),
+        1 => Some(33),
+        _ => None,
+    }
+}
+
+fn network_of(prefix: &str) -> &str {
+    PREFIXES.iter().find(|(known, _)| *known == prefix).map(|(_, network)| *network).unwrap_or(prefix)
+}
+
+// Base32 characters to their 5-bit values
+fn decode_body(body: &str) -> Result<Vec<u8>, AddressError> {
+    body.chars()
+        .map(|c| CHARSET.iter().position(|&known| known as char == c).map(|value| value as u8).ok_or(AddressError::InvalidCharacter(c)))
+        .collect()
+}
+
+fn checksum_ok(prefix: &str, body: &str) -> Option<bool> {
+    let values = decode_body(body).ok()?;
+    Some(values.len() > CHECKSUM_LEN && polymod(prefix, &values) == 0)
+}
+
+// Checksum over the low 5 bits of each prefix character, a zero separator, then the payload and checksum values.
+// A valid address gives 0, the same as computing it over the payload followed by eight zeros and comparing.
+fn polymod(prefix: &str, values: &[u8]) -> u64 {
+    let prefix = prefix.bytes().map(|byte| byte & 0x1f);
+    let mut checksum: u64 = 1;
+    for value in prefix.chain(std::iter::once(0)).chain(values.iter().copied()) {
+        let top = checksum >> 35;
+        checksum = ((checksum & 0x07_ffff_ffff) << 5) ^ value as u64;
+        for (bit, generator) in GENERATORS.iter().enumerate() {
+            if (top >> bit) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum ^ 1
+}
+
+// Regroup 5-bit values into bytes. The padding bits left over at the end have to be zero,
+// otherwise several strings would decode to the same address; None when they are not.
+fn from_base32(values: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(values.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for value in values {
+        buffer = (buffer << 5) | *value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    match buffer & ((1 << bits) - 1) {
+        0 => Some(bytes),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAINNET: &str = "kaspa:qrt3lf6jejjdzwtnvlr3z35w7j6q66gt49a7grdwsq98nmlg5uz97whuf8qfr";
+
+    // Base32 values with their checksum appended, as a full address
+    fn with_checksum(prefix: &str, mut values: Vec<u8>) -> String {
+        let checksum = polymod(prefix, &values.iter().copied().chain([0; CHECKSUM_LEN]).collect::<Vec<_>>());
+        values.extend((0..CHECKSUM_LEN).rev().map(|index| ((checksum >> (index * 5)) & 0x1f) as u8));
+        let body: String = values.iter().map(|value| CHARSET[*value as usize] as char).collect();
+        format!("{}:{}", prefix, body)
+    }
+
+    // Bytes regrouped into 5-bit values, the last one padded with zeros
+    fn to_base32(bytes: &[u8]) -> Vec<u8> {
+        let mut values = Vec::new();
+        let (mut buffer, mut bits) = (0u32, 0);
+        for byte in bytes {
+            buffer = (buffer << 8) | *byte as u32;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                values.push(((buffer >> bits) & 0x1f) as u8);
+            }
+        }
+        if bits > 0 {
+            values.push(((buffer << (5 - bits)) & 0x1f) as u8);
+        }
+        values
+    }
+
+    fn encode(prefix: &str, version: u8, payload: &[u8]) -> String {
+        let data: Vec<u8> = std::iter::once(version).chain(payload.iter().copied()).collect();
+        with_checksum(prefix, to_base32(&data))
+    }
+
+    #[test]
+    fn parses_a_known_mainnet_address() {
+        let address = Address::parse(MAINNET, "kaspa").unwrap();
+        assert_eq!(address.to_string(), MAINNET);
+        assert_eq!(address.version, 0);
+    }
+
+    #[test]
+    fn normalises_case_whitespace_and_a_missing_prefix() {
+        let body = MAINNET.strip_prefix("kaspa:").unwrap();
+        assert_eq!(Address::parse(&format!("  {}  ", MAINNET.to_uppercase()), "kaspa").unwrap().to_string(), MAINNET);
+        assert_eq!(Address::parse(body, "kaspa").unwrap().to_string(), MAINNET);
+    }
+
+    #[test]
+    fn accepts_every_address_version() {
+        for (version, length) in [(0, 32), (1, 33), (8, 32)] {
+            let input = encode("kaspatest", version, &vec![7; length]);
+            let address = Address::parse(&input, "kaspatest").unwrap();
+            assert_eq!((address.version, address.to_string()), (version, input));
+        }
+    }
+
+    #[test]
+    fn rejects_an_address_of_another_network() {
+        let expected = Err(AddressError::WrongNetwork { found: "kaspa".to_string(), expected: "kaspatest".to_string() });
+        assert_eq!(Address::parse(MAINNET, "kaspatest"), expected);
+        // Without a prefix the checksum still gives the network away
+        assert_eq!(Address::parse(MAINNET.strip_prefix("kaspa:").unwrap(), "kaspatest"), expected);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(Address::parse("   ", "kaspa"), Err(AddressError::Empty));
+        assert_eq!(Address::parse("bitcoin:qqq", "kaspa"), Err(AddressError::UnknownPrefix("bitcoin".to_string())));
+        assert_eq!(Address::parse(&MAINNET.replace("qrt3", "qrb3"), "kaspa"), Err(AddressError::InvalidCharacter('b')));
+        assert_eq!(Address::parse(&MAINNET[..MAINNET.len() - 1], "kaspa"), Err(AddressError::Length(60)));
+    }
+
+    #[test]
+    fn rejects_a_mistyped_character() {
+        let mistyped = MAINNET.replace("qrt3", "qrt4");
+        assert_eq!(Address::parse(&mistyped, "kaspa"), Err(AddressError::Checksum));
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_payload_lengths() {
+        assert_eq!(Address::parse(&encode("kaspa", 2, &[1; 32]), "kaspa"), Err(AddressError::Version(2)));
+        assert_eq!(
+            Address::parse(&encode("kaspa", 0, &[1; 33]), "kaspa"),
+            Err(AddressError::PayloadLength { version: 0, length: 33 }),
+        );
+    }
+
+    #[test]
+    fn rejects_non_zero_padding_bits() {
+        // 33 bytes take 53 values with one bit of padding at the end
+        let mut values = to_base32(&[0; 33]);
+        *values.last_mut().unwrap() |= 1;
+        assert_eq!(Address::parse(&with_checksum("kaspa", values), "kaspa"), Err(AddressError::Padding));
+    }
+}
@@ -0,0 +1,281 @@
+// src/amount.rs
+use std::fmt;
+
+const MAX_DECIMALS: u32 = 18; // KRC20 tokens have between 0 and 18 decimals
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountError {
+    Invalid(String),  // Not an unsigned integer, or too large for u128
+    Decimals(String), // Not a number between 0 and 18
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::Invalid(raw) => write!(f, "invalid amount {:?}", raw),
+            AmountError::Decimals(raw) => write!(f, "invalid decimals {:?}", raw),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+// A KRC20 amount as the API returns it: the raw integer in the token's smallest unit and the token's decimals.
+// All arithmetic is done on the integer so nothing is lost to floating point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenAmount {
+    raw: u128,
+    decimals: u32,
+}
+
+impl TokenAmount {
+    pub fn new(raw: u128, decimals: u32) -> Self {
+        TokenAmount { raw, decimals: decimals.min(MAX_DECIMALS) }
+    }
+
+    pub fn zero(decimals: u32) -> Self {
+        Self::new(0, decimals)
+    }
+
+    // Parse a raw amount string, e.g. `max` or a holder's `amount`
+    pub fn parse(raw: &str, decimals: u32) -> Result<Self, AmountError> {
+        raw.trim().parse::<u128>()
+            .map(|raw| Self::new(raw, decimals))
+            .map_err(|_| AmountError::Invalid(raw.to_string()))
+    }
+
+    // Parse the `dec` field of a token
+    pub fn parse_decimals(dec: &str) -> Result<u32, AmountError> {
+        match dec.trim().parse::<u32>() {
+            Ok(decimals) if decimals <= MAX_DECIMALS => Ok(decimals),
+            _ => Err(AmountError::Decimals(dec.to_string())),
+        }
+    }
+
+    // Sum of amounts of the same token
+    pub fn sum<'a>(decimals: u32, amounts: impl IntoIterator<Item = &'a TokenAmount>) -> Self {
+        amounts.into_iter().fold(Self::zero(decimals), |total, amount| total.saturating_add(amount))
+    }
+
+    pub fn raw(&self) -> u128 {
+        self.raw
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.raw == 0
+    }
+
+    pub fn saturating_add(&self, other: &TokenAmount) -> Self {
+        Self::new(self.raw.saturating_add(other.rescaled(self.decimals)), self.decimals)
+    }
+
+    // This amount as a share of `whole`; None when `whole` is zero
+    pub fn percent_of(&self, whole: &TokenAmount) -> Option<Percent> {
+        let whole = whole.rescaled(self.decimals);
+        if whole == 0 {
+            return None;
+        }
+        // Hundredths of a percent: amount * 10,000 / whole
+        Some(Percent(div_round(self.raw, whole, 4)))
+    }
+
+    // The amount in units of 10^power whole tokens with `digits` decimals, rounded half up.
+    // E.g. 1,234,567 tokens with power 6 and 2 digits is 123 (1.23M).
+    pub fn scaled(&self, power: u32, digits: u32) -> u128 {
+        div_round(self.raw, 10u128.pow(self.decimals + power), digits)
+    }
+
+    // The raw integer brought to another number of decimals, rounding down when decimals are dropped
+    fn rescaled(&self, decimals: u32) -> u128 {
+        if decimals >= self.decimals {
+            self.raw.saturating_mul(10u128.pow(decimals - self.decimals))
+        } else {
+            self.raw / 10u128.pow(self.decimals - decimals)
+        }
+    }
+}
+
+// The exact amount in whole tokens, without trailing zeros, e.g. `1234.5`
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10u128.pow(self.decimals);
+        let whole = self.raw / scale;
+        let fraction = self.raw % scale;
+        if fraction == 0 {
+            return write!(f, "{}", whole);
+        }
+        let digits = format!("{:0width$}", fraction, width = self.decimals as usize);
+        write!(f, "{}.{}", whole, digits.trim_end_matches('0'))
+    }
+}
+
+// Parses the amounts of one token for a reply. A malformed value is logged here, once, and the
+// formatter only shows it as N/A, so a bad `dec` does not log again for every row it spoils.
+pub struct TokenAmounts<'a> {
+    tick: &'a str,
+    decimals: Result<u32, AmountError>,
+}
+
+impl<'a> TokenAmounts<'a> {
+    pub fn new(tick: &'a str, dec: &str) -> Self {
+        let decimals = TokenAmount::parse_decimals(dec);
+        if let Err(e) = &decimals {
+            println!("Skipping the amounts of {}: {}", tick, e);
+        }
+        TokenAmounts { tick, decimals }
+    }
+
+    pub fn decimals(&self) -> Result<u32, AmountError> {
+        self.decimals.clone()
+    }
+
+    // One amount of the token; with malformed decimals every amount fails without logging again
+    pub fn parse(&self, raw: &str) -> Result<TokenAmount, AmountError> {
+        let decimals = self.decimals()?;
+        TokenAmount::parse(raw, decimals).map_err(|e| {
+            println!("Skipping an amount of {}: {}", self.tick, e);
+            e
+        })
+    }
+
+    // The `pre` field, where an empty value means nothing was pre-allocated
+    pub fn parse_pre(&self, raw: &str) -> Result<TokenAmount, AmountError> {
+        match raw.trim().is_empty() {
+            true => self.decimals().map(TokenAmount::zero),
+            false => self.parse(raw),
+        }
+    }
+}
+
+// A percentage kept in hundredths of a percent, e.g. 12.34% is 1234
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Percent(u128);
+
+impl Percent {
+    pub fn hundredths(&self) -> u128 {
+        self.0
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}%", self.0 / 100, self.0 % 100)
+    }
+}
+
+// numerator * 10^digits / denominator, rounded half up, by long division so nothing overflows
+fn div_round(numerator: u128, denominator: u128, digits: u32) -> u128 {
+    let (mut numerator, mut denominator) = (numerator, denominator);
+    // Keep room for one more digit in the remainder; this only drops bits far below the shown precision
+    while denominator > u128::MAX / 10 {
+        numerator >>= 4;
+        denominator >>= 4;
+    }
+
+    let mut quotient = numerator / denominator;
+    let mut remainder = numerator % denominator;
+    for _ in 0..digits {
+        remainder *= 10;
+        quotient = quotient.saturating_mul(10).saturating_add(remainder / denominator);
+        remainder %= denominator;
+    }
+    if remainder * 2 >= denominator {
+        quotient = quotient.saturating_add(1);
+    }
+    quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OVERFLOW: &str = "340282366920938463463374607431768211456"; // u128::MAX + 1
+
+    fn percent(part: TokenAmount, whole: TokenAmount) -> Option<u128> {
+        part.percent_of(&whole).map(|percent| percent.hundredths())
+    }
+
+    #[test]
+    fn parses_amounts_without_decimals() {
+        let amount = TokenAmount::parse(" 123 ", 0).unwrap();
+        assert_eq!((amount.raw(), amount.to_string()), (123, "123".to_string()));
+        assert_eq!(TokenAmount::zero(0).to_string(), "0");
+    }
+
+    #[test]
+    fn parses_amounts_with_18_decimals() {
+        assert_eq!(TokenAmount::parse("1500000000000000000", 18).unwrap().to_string(), "1.5");
+        assert_eq!(TokenAmount::parse("1", 18).unwrap().to_string(), "0.000000000000000001");
+        assert_eq!(TokenAmount::new(1_000_500, 6).to_string(), "1.0005");
+    }
+
+    #[test]
+    fn checks_the_decimals() {
+        assert_eq!(TokenAmount::parse_decimals("0"), Ok(0));
+        assert_eq!(TokenAmount::parse_decimals(" 18 "), Ok(18));
+        for dec in ["19", "-1", "", "eight"] {
+            assert_eq!(TokenAmount::parse_decimals(dec), Err(AmountError::Decimals(dec.to_string())));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_and_overflowing_amounts() {
+        assert_eq!(TokenAmount::parse(&u128::MAX.to_string(), 8).unwrap().raw(), u128::MAX);
+        for raw in [OVERFLOW, "-5", "1.5", "", "abc"] {
+            assert_eq!(TokenAmount::parse(raw, 8), Err(AmountError::Invalid(raw.to_string())));
+        }
+    }
+
+    #[test]
+    fn sums_saturate_instead_of_overflowing() {
+        let amounts = [TokenAmount::new(u128::MAX, 0), TokenAmount::new(1, 0)];
+        assert_eq!(TokenAmount::sum(0, amounts.iter()).raw(), u128::MAX);
+        // Amounts with other decimals are brought to the sum's decimals
+        assert_eq!(TokenAmount::sum(2, [TokenAmount::new(5, 0)].iter()).raw(), 500);
+    }
+
+    #[test]
+    fn rounds_percentages_half_up() {
+        assert_eq!(percent(TokenAmount::new(1, 0), TokenAmount::new(3, 0)), Some(3333));
+        assert_eq!(percent(TokenAmount::new(2, 0), TokenAmount::new(3, 0)), Some(6667));
+        assert_eq!(percent(TokenAmount::new(1, 0), TokenAmount::new(20_000, 0)), Some(1)); // 0.005% rounds up to 0.01%
+        assert_eq!(percent(TokenAmount::new(1, 0), TokenAmount::new(20_001, 0)), Some(0));
+        assert_eq!(percent(TokenAmount::new(5, 0), TokenAmount::zero(0)), None);
+    }
+
+    #[test]
+    fn compares_percentages_across_decimals_and_at_the_limits() {
+        assert_eq!(percent(TokenAmount::new(50, 0), TokenAmount::new(10_000, 2)), Some(5000));
+        assert_eq!(percent(TokenAmount::new(u128::MAX, 18), TokenAmount::new(u128::MAX, 18)), Some(10_000));
+        assert_eq!(percent(TokenAmount::new(u128::MAX / 2, 18), TokenAmount::new(u128::MAX, 18)), Some(5000));
+    }
+
+    #[test]
+    fn scales_to_units_rounding_half_up() {
+        let amount = TokenAmount::new(1_234_567 * 100_000_000, 8);
+        assert_eq!(amount.scaled(6, 2), 123);
+        assert_eq!(amount.scaled(3, 0), 1235);
+        assert_eq!(TokenAmount::new(1_235_000, 0).scaled(6, 2), 124);
+        assert_eq!(TokenAmount::new(u128::MAX, 0).scaled(15, 0), u128::MAX / 10u128.pow(15) + 1);
+    }
+
+    #[test]
+    fn token_amounts_read_an_empty_pre_allocation_as_zero() {
+        let amounts = TokenAmounts::new("NACHO", "8");
+        assert_eq!(amounts.parse_pre(" "), Ok(TokenAmount::zero(8)));
+        assert_eq!(amounts.parse_pre("100"), Ok(TokenAmount::new(100, 8)));
+        assert!(amounts.parse_pre("n/a").is_err());
+
+        let malformed = TokenAmounts::new("NACHO", "x");
+        assert_eq!(malformed.parse("100"), Err(AmountError::Decimals("x".to_string())));
+        assert_eq!(malformed.parse_pre(""), Err(AmountError::Decimals("x".to_string())));
+    }
+}
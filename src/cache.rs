@@ -0,0 +1,250 @@
+// src/cache.rs
+use crate::imports::*;
+use crate::kasplex::KasplexError;
+use crate::storage::Storage;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+// The Kasplex endpoints we cache; each one has its own time to live
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Endpoint {
+    TokenInfo,        // /token/{tick}?stat=true
+    HolderList,       // /token/{tick}?holder=true
+    AddressBalances,  // /address/{addr}/tokenlist
+}
+
+impl Endpoint {
+    // Stable name used when the cache is saved to the database
+    pub fn name(&self) -> &'static str {
+        match self {
+            Endpoint::TokenInfo => "token_info",
+            Endpoint::HolderList => "holder_list",
+            Endpoint::AddressBalances => "address_balances",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Endpoint::TokenInfo, Endpoint::HolderList, Endpoint::AddressBalances]
+            .into_iter()
+            .find(|endpoint| endpoint.name() == name)
+    }
+}
+
+// How long a cached response counts as fresh, per endpoint
+#[derive(Debug, Clone)]
+pub struct CacheTtls {
+    pub token_info: Duration,
+    pub holder_list: Duration,
+    pub address_balances: Duration,
+}
+
+impl CacheTtls {
+    pub fn get(&self, endpoint: Endpoint) -> Duration {
+        match endpoint {
+            Endpoint::TokenInfo => self.token_info,
+            Endpoint::HolderList => self.holder_list,
+            Endpoint::AddressBalances => self.address_balances,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub network: String,
+    pub endpoint: Endpoint,
+    pub id: String, // Ticker or address
+}
+
+impl CacheKey {
+    pub fn new(network: &str, endpoint: Endpoint, id: &str) -> Self {
+        CacheKey { network: network.to_string(), endpoint, id: id.to_string() }
+    }
+}
+
+// A cached response, kept as JSON so every endpoint shares one store and one snapshot format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub value: serde_json::Value,
+    pub fetched_at: u64, // Seconds since the UNIX epoch
+}
+
+// A value read through the cache
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    pub value: T,
+    pub fetched_at: u64,
+    pub stale: bool, // The API failed and this is an expired entry
+    pub fetched: bool, // The API was called for this value just now
+}
+
+#[async_trait]
+pub trait Cache: Send + Sync {
+    // The entry for a key, whether or not it is still fresh
+    async fn get(&self, key: &CacheKey) -> Option<Entry>;
+
+    async fn insert(&self, key: CacheKey, entry: Entry);
+
+    // Time to live for an endpoint
+    fn ttl(&self, endpoint: Endpoint) -> Duration;
+
+    // Held while a key is being fetched so concurrent callers wait for that fetch instead of repeating it
+    async fn flight(&self, key: &CacheKey) -> tokio::sync::OwnedMutexGuard<()>;
+
+    // Ids cached for a network and endpoint, e.g. the tickers for autocomplete
+    async fn ids(&self, network: &str, endpoint: Endpoint) -> Vec<String>;
+
+    // Drop entries older than max_age, they are too old even to serve as stale data
+    async fn prune(&self, max_age: Duration);
+}
+
+// Seconds since the UNIX epoch
+pub fn current_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs()
+}
+
+// Return the cached value while it is fresh, otherwise fetch it once for all concurrent callers.
+// When the API is unavailable an expired entry is returned marked as stale.
+pub async fn get_or_fetch<T, F, Fut>(cache: &dyn Cache, key: CacheKey, fetch: F) -> Result<Cached<T>, KasplexError>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, KasplexError>>,
+{
+    let ttl = cache.ttl(key.endpoint).as_secs();
+    if let Some(cached) = fresh(cache.get(&key).await, ttl) {
+        return Ok(cached);
+    }
+
+    let _flight = cache.flight(&key).await;
+    // Another caller may have filled the entry while we waited
+    let existing = cache.get(&key).await;
+    if let Some(cached) = fresh(existing.clone(), ttl) {
+        return Ok(cached);
+    }
+
+    match fetch().await {
+        Ok(value) => {
+            let fetched_at = current_time();
+            match serde_json::to_value(&value) {
+                Ok(json) => cache.insert(key, Entry { value: json, fetched_at }).await,
+                Err(e) => println!("Failed to cache {:?} {}: {}", key.endpoint, key.id, e),
+            }
+            Ok(Cached { value, fetched_at, stale: false, fetched: true })
+        },
+        Err(e) if e.is_unavailable() => {
+            match existing.and_then(|entry| decode(entry, true)) {
+                Some(cached) => {
+                    println!("Serving stale {:?} {} on {}: {}", key.endpoint, key.id, key.network, e);
+                    Ok(cached)
+                },
+                None => Err(e),
+            }
+        },
+        Err(e) => Err(e),
+    }
+}
+
+fn fresh<T: DeserializeOwned>(entry: Option<Entry>, ttl: u64) -> Option<Cached<T>> {
+    entry
+        .filter(|entry| current_time() < entry.fetched_at + ttl)
+        .and_then(|entry| decode(entry, false))
+}
+
+// An entry that no longer matches the type is treated as missing
+fn decode<T: DeserializeOwned>(entry: Entry, stale: bool) -> Option<Cached<T>> {
+    let value = serde_json::from_value(entry.value).ok()?;
+    Some(Cached { value, fetched_at: entry.fetched_at, stale, fetched: false })
+}
+
+// Process-local cache, optionally saved to the database so a restart starts warm
+pub struct MemoryCache {
+    entries: RwLock<HashMap<CacheKey, Entry>>,
+    flights: Mutex<HashMap<CacheKey, Arc<tokio::sync::Mutex<()>>>>,
+    ttls: CacheTtls,
+}
+
+impl MemoryCache {
+    pub fn new(ttls: CacheTtls) -> Self {
+        MemoryCache {
+            entries: RwLock::new(HashMap::new()),
+            flights: Mutex::new(HashMap::new()),
+            ttls,
+        }
+    }
+
+    // Fill the cache from a saved snapshot so a restart starts warm
+    pub fn extend(&self, entries: Vec<(CacheKey, Entry)>) {
+        self.entries.write().unwrap().extend(entries);
+    }
+
+    // Copy of every entry, for saving a snapshot
+    pub fn entries(&self) -> Vec<(CacheKey, Entry)> {
+        self.entries.read().unwrap()
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &CacheKey) -> Option<Entry> {
+        self.entries.read().unwrap().get(key).cloned()
+    }
+
+    async fn insert(&self, key: CacheKey, entry: Entry) {
+        self.entries.write().unwrap().insert(key, entry);
+    }
+
+    fn ttl(&self, endpoint: Endpoint) -> Duration {
+        self.ttls.get(endpoint)
+    }
+
+    async fn flight(&self, key: &CacheKey) -> tokio::sync::OwnedMutexGuard<()> {
+        let lock = {
+            let mut flights = self.flights.lock().unwrap();
+            // Forget locks nobody holds or waits for, so the map does not grow with every key ever seen
+            flights.retain(|_, lock| Arc::strong_count(lock) > 1);
+            flights.entry(key.clone()).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+
+    async fn ids(&self, network: &str, endpoint: Endpoint) -> Vec<String> {
+        let mut ids: Vec<String> = self.entries.read().unwrap()
+            .keys()
+            .filter(|key| key.network == network && key.endpoint == endpoint)
+            .map(|key| key.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    async fn prune(&self, max_age: Duration) {
+        let oldest = current_time().saturating_sub(max_age.as_secs());
+        self.entries.write().unwrap().retain(|_, entry| entry.fetched_at >= oldest);
+    }
+}
+
+// Periodically drop expired entries and, when a database is given, save the snapshot to it
+pub fn spawn_maintenance(cache: Arc<MemoryCache>, interval: Duration, max_age: Duration, snapshot: Option<Storage>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await; // The first tick fires immediately, right after the snapshot was loaded
+        loop {
+            ticker.tick().await;
+            cache.prune(max_age).await;
+            if let Some(storage) = &snapshot {
+                if let Err(e) = storage.save_cache_entries(cache.entries()).await {
+                    println!("Failed to save the cache snapshot: {}", e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,232 @@
+// src/charts.rs
+use crate::result_struct::Holder;
+use crate::storage::HistorySample;
+use chrono::{DateTime, Duration as ChronoDuration, TimeZone, Utc};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::{register_font, FontStyle};
+use std::fmt;
+use std::sync::Once;
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 400;
+const FONT: &str = "sans-serif";
+const FONT_DATA: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf"); // Bundled so the charts render without system fonts
+const BACKGROUND: RGBColor = RGBColor(0x2b, 0x2d, 0x31); // Discord's dark embed background
+const TEXT: RGBColor = RGBColor(0xdb, 0xde, 0xe1);
+const GRID: RGBColor = RGBColor(0x3f, 0x41, 0x47);
+const TOP_HOLDERS: usize = 10; // Bars in the distribution chart
+
+static FONT_REGISTERED: Once = Once::new();
+
+// Which chart to draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Mint,         // % of the max supply minted over time
+    Holders,      // Holder count over time
+    Distribution, // Share of the minted supply held by the top holders
+}
+
+impl Metric {
+    pub const NAMES: &'static [&'static str] = &["mint", "holders", "distribution"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "mint" | "progress" => Some(Metric::Mint),
+            "holders" => Some(Metric::Holders),
+            "distribution" | "top" => Some(Metric::Distribution),
+            _ => None,
+        }
+    }
+}
+
+// How far back a time series chart goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Range {
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl Range {
+    pub const NAMES: &'static [&'static str] = &["24h", "7d", "30d", "all"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "24h" | "1d" => Some(Range::Day),
+            "7d" | "1w" => Some(Range::Week),
+            "30d" | "1m" => Some(Range::Month),
+            "all" => Some(Range::All),
+            _ => None,
+        }
+    }
+
+    // Length of the range in seconds, None for everything recorded
+    pub fn secs(&self) -> Option<u64> {
+        match self {
+            Range::Day => Some(24 * 3600),
+            Range::Week => Some(7 * 24 * 3600),
+            Range::Month => Some(30 * 24 * 3600),
+            Range::All => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ChartError(String);
+
+impl fmt::Display for ChartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to render chart: {}", self.0)
+    }
+}
+
+impl std::error::Error for ChartError {}
+
+impl<E: std::error::Error + Send + Sync> From<DrawingAreaErrorKind<E>> for ChartError {
+    fn from(e: DrawingAreaErrorKind<E>) -> Self {
+        ChartError(e.to_string())
+    }
+}
+
+// % of the max supply minted at each sample; the raw max supply has to be known and above zero
+pub fn mint_progress(tick: &str, max: u128, samples: &[HistorySample], color: u32) -> Result<Vec<u8>, ChartError> {
+    if max == 0 {
+        return Err(ChartError(format!("no max supply to chart the mint progress of {}", tick)));
+    }
+    let points: Vec<(DateTime<Utc>, f64)> = samples.iter()
+        .map(|sample| (timestamp(sample.taken_at), sample.minted as f64 / max as f64 * 100.0))
+        .collect();
+    render(|root| time_series(root, &format!("{} % minted", tick), "% minted", &points, color))
+}
+
+// Holder count at each sample that has one
+pub fn holder_count(tick: &str, samples: &[HistorySample], color: u32) -> Result<Vec<u8>, ChartError> {
+    let points: Vec<(DateTime<Utc>, f64)> = samples.iter()
+        .filter_map(|sample| sample.holder_total.map(|holders| (timestamp(sample.taken_at), holders as f64)))
+        .collect();
+    render(|root| time_series(root, &format!("{} holders", tick), "Holders", &points, color))
+}
+
+// Share of the minted supply held by each of the top holders
+pub fn distribution(tick: &str, holders: &[Holder], minted: u128, color: u32) -> Result<Vec<u8>, ChartError> {
+    let shares: Vec<f64> = holders.iter()
+        .take(TOP_HOLDERS)
+        .map(|holder| match minted {
+            0 => 0.0,
+            _ => holder.amount.parse::<u128>().unwrap_or(0) as f64 / minted as f64 * 100.0,
+        })
+        .collect();
+
+    render(|root| {
+        let top = shares.iter().cloned().fold(1.0, f64::max) * 1.1;
+        let ranks = shares.len().max(1) as u32;
+        let mut chart = ChartBuilder::on(root)
+            .caption(format!("{} top {} holders", tick, shares.len()), (FONT, 24).into_font().color(&TEXT))
+            .margin(16)
+            .x_label_area_size(36)
+            .y_label_area_size(64)
+            .build_cartesian_2d((1..ranks.max(2)).into_segmented(), 0.0..top)?; // A single segment has no width
+
+        chart.configure_mesh()
+            .disable_x_mesh()
+            .light_line_style(GRID)
+            .bold_line_style(GRID)
+            .axis_style(TEXT)
+            .label_style((FONT, 14).into_font().color(&TEXT))
+            .x_labels(ranks as usize)
+            .x_label_formatter(&|rank| match rank {
+                SegmentValue::CenterOf(rank) | SegmentValue::Exact(rank) => format!("#{}", rank),
+                SegmentValue::Last => String::new(),
+            })
+            .y_desc("% of minted")
+            .y_label_formatter(&|y| format!("{:.1}%", y))
+            .draw()?;
+
+        chart.draw_series(shares.iter().enumerate().map(|(index, share)| {
+            let rank = index as u32 + 1;
+            let mut bar = Rectangle::new([(SegmentValue::Exact(rank), 0.0), (SegmentValue::Exact(rank + 1), *share)], rgb(color).filled());
+            bar.set_margin(0, 0, 8, 8);
+            bar
+        }))?;
+        Ok(())
+    })
+}
+
+fn time_series(root: &DrawingArea<BitMapBackend, Shift>, title: &str, y_desc: &str, points: &[(DateTime<Utc>, f64)], color: u32) -> Result<(), ChartError> {
+    let (start, end) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) if last.0 > first.0 => (first.0, last.0),
+        (Some(first), _) => (first.0 - ChronoDuration::hours(1), first.0 + ChronoDuration::hours(1)),
+        _ => return Err(ChartError("no data points".to_string())),
+    };
+
+    // Pad the value axis so a flat line does not sit on the border
+    let low = points.iter().map(|point| point.1).fold(f64::INFINITY, f64::min);
+    let high = points.iter().map(|point| point.1).fold(f64::NEG_INFINITY, f64::max);
+    let padding = ((high - low) * 0.1).max(high.abs() * 0.01).max(1.0);
+    let span = end - start;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, (FONT, 24).into_font().color(&TEXT))
+        .margin(16)
+        .x_label_area_size(36)
+        .y_label_area_size(72)
+        .build_cartesian_2d(start..end, (low - padding).max(0.0)..high + padding)?;
+
+    chart.configure_mesh()
+        .light_line_style(GRID)
+        .bold_line_style(GRID)
+        .axis_style(TEXT)
+        .label_style((FONT, 14).into_font().color(&TEXT))
+        .x_labels(6)
+        .x_label_formatter(&|time| {
+            if span > ChronoDuration::days(7) {
+                time.format("%b %d").to_string()
+            } else if span > ChronoDuration::days(1) {
+                time.format("%b %d %H:%M").to_string()
+            } else {
+                time.format("%H:%M").to_string()
+            }
+        })
+        .y_desc(y_desc)
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(points.iter().cloned(), rgb(color).stroke_width(2)))?;
+    Ok(())
+}
+
+// Draw on an in-memory bitmap and encode it as PNG
+fn render(draw: impl FnOnce(&DrawingArea<BitMapBackend, Shift>) -> Result<(), ChartError>) -> Result<Vec<u8>, ChartError> {
+    FONT_REGISTERED.call_once(|| {
+        if register_font(FONT, FontStyle::Normal, FONT_DATA).is_err() {
+            println!("Failed to load the chart font");
+        }
+    });
+
+    let mut pixels = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&BACKGROUND)?;
+        draw(&root)?;
+        root.present()?;
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, WIDTH, HEIGHT);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| ChartError(e.to_string()))?;
+    writer.write_image_data(&pixels).map_err(|e| ChartError(e.to_string()))?;
+    writer.finish().map_err(|e| ChartError(e.to_string()))?;
+    Ok(png)
+}
+
+fn timestamp(secs: u64) -> DateTime<Utc> {
+    Utc.timestamp_opt(secs as i64, 0).single().unwrap_or_default()
+}
+
+// Theme colours are stored as 0xRRGGBB
+fn rgb(color: u32) -> RGBColor {
+    RGBColor((color >> 16) as u8, (color >> 8) as u8, color as u8)
+}
@@ -0,0 +1,42 @@
+// src/commands/api_error.rs
+use crate::kasplex::KasplexError;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::theme;
+
+// Turn a failed Kasplex call into an embed that tells the user what went wrong and on which network
+pub fn api_error_reply(error: &KasplexError, usage: &str, network: &Network) -> Reply {
+    let (title, description) = match error {
+        KasplexError::UnknownTicker(tick) => (
+            "Invalid token".to_string(),
+            format!("`{}` is not a known KRC20 token. Make sure to provide a valid token for: `{}`!", tick.to_uppercase(), usage),
+        ),
+        KasplexError::RateLimited { retry_after } => (
+            "Too many requests".to_string(),
+            match retry_after {
+                Some(seconds) => format!("The Kasplex API is rate limiting the bot. Please try again in {} seconds.", seconds),
+                None => "The Kasplex API is rate limiting the bot. Please try again in a minute.".to_string(),
+            },
+        ),
+        KasplexError::ApiDown(_) | KasplexError::CircuitOpen => (
+            "Kasplex API unavailable".to_string(),
+            "The Kasplex API is not responding right now. Please try again in a few minutes.".to_string(),
+        ),
+        KasplexError::Malformed(_) => (
+            "Unexpected API response".to_string(),
+            "The Kasplex API sent data the bot could not read. Please try again later.".to_string(),
+        ),
+        KasplexError::NotFound(message) | KasplexError::Api { message, .. } => (
+            "Request rejected".to_string(),
+            if message.is_empty() {
+                format!("The Kasplex API rejected the request. Make sure to use the correct format for: `{}`!", usage)
+            } else {
+                format!("The Kasplex API says: {}. Make sure to use the correct format for: `{}`!", message, usage)
+            },
+        ),
+    };
+
+    Reply::new().embed(theme::current().embed()
+        .description(format!("Network: **{}**", network.label))
+        .field(title, description, false))
+}
@@ -0,0 +1,178 @@
+// src/commands/chart.rs
+use crate::imports::*;
+use crate::amount::TokenAmount;
+use crate::cache::Cached;
+use crate::charts::{self, Metric, Range};
+use crate::commands::api_error::api_error_reply;
+use crate::commands::registry::{ArgKind, ArgSpec, Command, Invocation, NETWORK_FLAG};
+use crate::mint_status::datatweaks;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::state::BotState;
+use crate::storage::HistorySample;
+use crate::theme;
+use crate::DataStruct;
+
+const CHART_ARGS: &[ArgSpec] = &[
+    ArgSpec::optional("ticker", "KRC20 ticker, e.g. NACHO (default: the server's ticker)", ArgKind::Ticker),
+    ArgSpec::optional("metric", "What to chart: mint, holders or distribution", ArgKind::Choice(Metric::NAMES)),
+    ArgSpec::optional("range", "How far back to go: 24h, 7d, 30d or all", ArgKind::Choice(Range::NAMES)),
+    NETWORK_FLAG,
+];
+
+// Why a chart could not be drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartUnavailable {
+    NotEnoughHistory,
+    UnknownMaxSupply, // The API left out the max supply, sent it malformed or as zero
+    Failed,           // Reading the history or drawing failed, already logged
+}
+
+pub struct ChartCommand;
+
+#[async_trait]
+impl Command for ChartCommand {
+    fn name(&self) -> &'static str {
+        "tokenchart"
+    }
+
+    fn description(&self) -> &'static str {
+        "Draws a chart of the mint progress, holder count or top holders of a token."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        CHART_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+        let args = &invocation.args;
+        let metric = args.get("metric").map(Metric::parse).unwrap_or(Some(Metric::Mint));
+        let range = args.get("range").map(Range::parse).unwrap_or(Some(Range::Week));
+
+        match (invocation.ticker(), metric, range) {
+            (Some(ticker), Some(metric), Some(range)) => Some(chart_reply(invocation.state, network, &ticker, metric, range).await),
+            _ => Some(usage_reply()),
+        }
+    }
+}
+
+async fn chart_reply(state: &BotState, network: &Network, ticker: &str, metric: Metric, range: Range) -> Reply {
+    let theme = theme::current();
+    let token = ticker.to_uppercase();
+
+    let data = match datatweaks::view_token(state.cache.as_ref(), &state.storage, network, &token).await {
+        Ok(data) => data,
+        Err(e) => {
+            println!("Failed to fetch token data: {}", e);
+            return api_error_reply(&e, "!tokenchart [ticker] [metric] [range]", network);
+        }
+    };
+
+    let (title, message) = match chart_attachment(state, network, &data, metric, range).await {
+        Ok(file) => return Reply::new()
+            .content(format!("**# {} on {}**", token, network.label))
+            .attachment(file),
+        Err(ChartUnavailable::NotEnoughHistory) => (
+            "Not enough history",
+            format!("There is not enough recorded data to chart {} yet. Try again later or pick a longer range.", token),
+        ),
+        Err(ChartUnavailable::UnknownMaxSupply) => (
+            "Max supply unavailable",
+            format!("The Kasplex API did not send a usable max supply for {}, so its mint progress cannot be charted.", token),
+        ),
+        Err(ChartUnavailable::Failed) => (
+            "Chart unavailable",
+            format!("The chart of {} could not be drawn. Please try again later.", token),
+        ),
+    };
+    Reply::new().embed(theme.embed()
+        .description(format!("Network: **{}**", network.label))
+        .field(title, message, false))
+}
+
+// Render a chart of a token as a PNG attachment, or tell why it cannot be drawn
+pub async fn chart_attachment(state: &BotState, network: &Network, data: &Cached<DataStruct>, metric: Metric, range: Range) -> Result<CreateAttachment, ChartUnavailable> {
+    let token = data.value.result[0].clone();
+    let theme = theme::current();
+    let color = theme.status_color.unwrap_or(theme.color);
+
+    let history = match metric {
+        Metric::Distribution => Vec::new(),
+        Metric::Mint | Metric::Holders => {
+            let since = range.secs().map(|secs| data.fetched_at.saturating_sub(secs)).unwrap_or(0);
+            let mut history = match state.storage.token_history(&network.name, &token.tick, since).await {
+                Ok(history) => history,
+                Err(e) => {
+                    println!("Failed to read the history of {}: {}", token.tick, e);
+                    return Err(ChartUnavailable::Failed);
+                }
+            };
+            // End the line at the data being shown, unless that snapshot is already recorded
+            if !matches!(history.last(), Some(last) if last.taken_at >= data.fetched_at) {
+                history.push(HistorySample::from_token(&token, data.fetched_at));
+            }
+            if history.len() < 2 {
+                return Err(ChartUnavailable::NotEnoughHistory);
+            }
+            history
+        },
+    };
+
+    let tick = token.tick.to_uppercase();
+    // Raw amounts, like the recorded history; an amount the API sent malformed is unknown rather than zero
+    let decimals = TokenAmount::parse_decimals(&token.dec);
+    let amount = |raw: &str| decimals.clone().and_then(|dec| TokenAmount::parse(raw, dec)).ok().map(|amount| amount.raw());
+    let max = amount(&token.max).filter(|max| *max > 0);
+    let minted = amount(&token.minted).unwrap_or(0);
+    let holders = token.holder.unwrap_or_default();
+    match metric {
+        Metric::Mint if max.is_none() => return Err(ChartUnavailable::UnknownMaxSupply),
+        Metric::Distribution if holders.is_empty() => return Err(ChartUnavailable::NotEnoughHistory),
+        _ => {},
+    }
+
+    // Drawing is CPU bound, keep it off the event loop
+    let rendered = tokio::task::spawn_blocking(move || match metric {
+        Metric::Mint => charts::mint_progress(&tick, max.unwrap_or(0), &history, color),
+        Metric::Holders => charts::holder_count(&tick, &history, color),
+        Metric::Distribution => charts::distribution(&tick, &holders, minted, color),
+    })
+    .await;
+
+    match rendered {
+        Ok(Ok(png)) => Ok(CreateAttachment::bytes(png, format!("{}_{}.png", token.tick.to_lowercase(), metric_file_name(metric)))),
+        Ok(Err(e)) => {
+            println!("{}", e);
+            Err(ChartUnavailable::Failed)
+        },
+        Err(e) => {
+            println!("Chart task failed: {}", e);
+            Err(ChartUnavailable::Failed)
+        },
+    }
+}
+
+fn metric_file_name(metric: Metric) -> &'static str {
+    match metric {
+        Metric::Mint => "mint",
+        Metric::Holders => "holders",
+        Metric::Distribution => "distribution",
+    }
+}
+
+fn usage_reply() -> Reply {
+    Reply::new().embed(theme::current().embed()
+        .field(
+            "Wrong Number of Parameters",
+            format!(
+                "Make sure to use the correct format for: `!tokenchart [ticker] [metric] [range]`!\nMetrics: {}\nRanges: {}",
+                Metric::NAMES.join(", "),
+                Range::NAMES.join(", "),
+            ),
+            false,
+        ))
+}
@@ -0,0 +1,258 @@
+// src/commands/compare.rs
+use crate::imports::*;
+use crate::amount::{AmountError, TokenAmount, TokenAmounts};
+use crate::commands::api_error::api_error_reply;
+use crate::commands::registry::{ArgKind, ArgSpec, Command, Invocation, EXACT_FLAG, NETWORK_FLAG};
+use crate::format::{Field, NumberFormat};
+use crate::holder_status::analytics;
+use crate::kasplex::KasplexError;
+use crate::mint_status::datatweaks;
+use crate::reply::Reply;
+use crate::result_struct::ResultStruct;
+use crate::theme;
+use serenity::futures::future::join_all;
+
+const COMPARE_ARGS: &[ArgSpec] = &[
+    ArgSpec::required("tickers", "KRC20 tickers separated by spaces, e.g. NACHO KASPER KSPR", ArgKind::Text),
+    NETWORK_FLAG,
+    EXACT_FLAG,
+];
+const USAGE: &str = "!compare [ticker] [ticker] ...";
+const LEADER: &str = "⭐";
+
+// The measures compared side by side, in the order they are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Minted,
+    Holders,
+    Mints,
+    MaxSupply,
+    PreAllocation,
+    Top10,
+}
+
+impl Metric {
+    const ALL: [Metric; 6] = [Metric::Minted, Metric::Holders, Metric::Mints, Metric::MaxSupply, Metric::PreAllocation, Metric::Top10];
+
+    fn label(self) -> &'static str {
+        match self {
+            Metric::Minted => "Minted",
+            Metric::Holders => "Holders",
+            Metric::Mints => "Mints",
+            Metric::MaxSupply => "Max Supply",
+            Metric::PreAllocation => "Pre-Allocation",
+            Metric::Top10 => "Top 10 Share",
+        }
+    }
+
+    // Less pre-allocation and a less concentrated supply lead, for the rest more is better
+    fn highest_leads(self) -> bool {
+        !matches!(self, Metric::PreAllocation | Metric::Top10)
+    }
+
+    // The value tokens are ranked by and the text shown for it. The value is None when the API
+    // left the field out or sent it malformed, such a token is never the leader.
+    fn measure(self, format: &NumberFormat, token: &ResultStruct, amounts: &ParsedAmounts) -> (Option<u128>, String) {
+        let count = |count: &Option<String>| count.as_deref().and_then(|count| count.trim().parse::<u128>().ok());
+        let na = || "N/A".to_string();
+
+        match self {
+            Metric::Minted => match (&amounts.minted, &amounts.max) {
+                (Ok(minted), Ok(max)) => match minted.percent_of(max) {
+                    Some(percent) => (Some(percent.hundredths()), format.percent(percent)),
+                    None => (None, na()),
+                },
+                _ => (None, na()),
+            },
+            Metric::Holders => (count(&token.holderTotal), format_count(format, count(&token.holderTotal))),
+            Metric::Mints => (count(&token.mintTotal), format_count(format, count(&token.mintTotal))),
+            // Whole tokens, so supplies with different decimals compare fairly
+            Metric::MaxSupply => match &amounts.max {
+                Ok(max) => (Some(max.scaled(0, 0)), format.amount(max, Field::Supply)),
+                Err(_) => (None, na()),
+            },
+            Metric::PreAllocation => {
+                let text = datatweaks::format_pre_allocation(format, &amounts.pre);
+                match (&amounts.pre, &amounts.max) {
+                    (Ok(pre), _) if pre.is_zero() => (Some(0), text),
+                    (Ok(pre), Ok(max)) => match pre.percent_of(max) {
+                        Some(percent) => (Some(percent.hundredths()), format!("{} ({})", text, format.percent(percent))),
+                        None => (None, text),
+                    },
+                    _ => (None, text),
+                }
+            },
+            Metric::Top10 => {
+                let minted = amounts.minted.as_ref().map(TokenAmount::raw).unwrap_or(0);
+                let share = analytics::analyze(token.holder.as_deref().unwrap_or_default(), minted)
+                    .and_then(|analytics| analytics.bucket_shares.iter().find(|(size, _)| *size == 10).map(|(_, share)| *share));
+                match share {
+                    Some(share) => (Some((share * 100.0).round() as u128), format!("{}%", format.decimal(share, 2))),
+                    None => (None, na()),
+                }
+            },
+        }
+    }
+}
+
+// The amounts of one token, parsed once for all metrics so a malformed field is logged once
+struct ParsedAmounts {
+    max: Result<TokenAmount, AmountError>,
+    minted: Result<TokenAmount, AmountError>,
+    pre: Result<TokenAmount, AmountError>,
+}
+
+impl ParsedAmounts {
+    fn new(token: &ResultStruct) -> Self {
+        let amounts = TokenAmounts::new(&token.tick, &token.dec);
+        ParsedAmounts {
+            max: amounts.parse(&token.max),
+            minted: amounts.parse(&token.minted),
+            pre: amounts.parse_pre(&token.pre),
+        }
+    }
+}
+
+pub struct CompareCommand;
+
+#[async_trait]
+impl Command for CompareCommand {
+    fn name(&self) -> &'static str {
+        "compare"
+    }
+
+    fn description(&self) -> &'static str {
+        "Compares the mint status of several tokens side by side."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        COMPARE_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+        let state = invocation.state;
+        let max_tickers = state.config.compare_max_tickers;
+
+        // Commas work as separators too, and a ticker given twice is compared once
+        let mut tickers: Vec<String> = Vec::new();
+        for ticker in invocation.args.get("tickers").unwrap_or_default().split(|c: char| c == ',' || c.is_whitespace()) {
+            let ticker = ticker.trim().to_uppercase();
+            if !ticker.is_empty() && !tickers.contains(&ticker) {
+                tickers.push(ticker);
+            }
+        }
+        if tickers.len() < 2 {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Wrong Number of Parameters", format!("Give at least two tickers, e.g. `!compare NACHO KASPER`. Make sure to use the correct format for: `{}`!", USAGE), false)));
+        }
+        if tickers.len() > max_tickers {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Too many tokens", format!("Up to {} tokens can be compared at once.", max_tickers), false)));
+        }
+
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+
+        // All tokens are fetched at once through the cached status path
+        let results = join_all(tickers.iter()
+            .map(|ticker| datatweaks::view_token(state.cache.as_ref(), &state.storage, network, ticker))).await;
+
+        let mut tokens: Vec<ResultStruct> = Vec::new();
+        let mut missing: Vec<String> = Vec::new();
+        let mut errors: Vec<KasplexError> = Vec::new();
+        let mut stale = false;
+        for (ticker, result) in tickers.iter().zip(results) {
+            match result {
+                Ok(data) if data.value.result.first().is_some_and(|token| token.state != "unused") => {
+                    stale |= data.stale;
+                    tokens.push(data.value.result[0].clone());
+                },
+                Ok(_) => missing.push(format!("`{}` has not been deployed", ticker)),
+                Err(KasplexError::UnknownTicker(_)) => missing.push(format!("`{}` is not a known KRC20 token", ticker)),
+                Err(e) => {
+                    println!("Failed to fetch token data for {}: {}", ticker, e);
+                    missing.push(format!("`{}` could not be loaded", ticker));
+                    errors.push(e);
+                },
+            }
+        }
+
+        // Nothing to show, the API error explains why better than an empty comparison
+        if tokens.is_empty() {
+            if let Some(e) = errors.first() {
+                return Some(api_error_reply(e, USAGE, network));
+            }
+        }
+
+        Some(compare_reply(network.label.as_str(), &invocation.number_format(), &tokens, &missing, stale))
+    }
+}
+
+fn compare_reply(network: &str, format: &NumberFormat, tokens: &[ResultStruct], missing: &[String], stale: bool) -> Reply {
+    let measures: Vec<Vec<(Option<u128>, String)>> = tokens.iter()
+        .map(|token| {
+            let amounts = ParsedAmounts::new(token);
+            Metric::ALL.iter().map(|metric| metric.measure(format, token, &amounts)).collect()
+        })
+        .collect();
+
+    // The best value of each metric, only once there are two tokens to rank
+    let best: Vec<Option<u128>> = Metric::ALL.iter().enumerate()
+        .map(|(index, metric)| {
+            let values = measures.iter().filter_map(|token| token[index].0);
+            match (tokens.len() >= 2, metric.highest_leads()) {
+                (false, _) => None,
+                (true, true) => values.max(),
+                (true, false) => values.min(),
+            }
+        })
+        .collect();
+
+    let mut description = format!("Network: **{}**\n{} marks the leader on each metric.", network, LEADER);
+    if stale {
+        description = format!("⚠️ The Kasplex API is unavailable, data may be stale.\n{}", description);
+    }
+    let mut embed = theme::current().embed()
+        .title(format!("Comparing {}", tokens.iter().map(|token| token.tick.to_uppercase()).collect::<Vec<_>>().join(", ")))
+        .description(description);
+
+    for (token, values) in tokens.iter().zip(&measures) {
+        let lines: Vec<String> = Metric::ALL.iter().zip(values).zip(&best)
+            .map(|((metric, (value, text)), best)| match value.is_some() && value == best {
+                true => format!("{}: **{}** {}", metric.label(), text, LEADER),
+                false => format!("{}: {}", metric.label(), text),
+            })
+            .collect();
+        embed = embed.field(token.tick.to_uppercase(), lines.join("\n"), true);
+    }
+
+    // Every token sharing the best value leads, e.g. several fair launches
+    let leaders: Vec<String> = Metric::ALL.iter().enumerate()
+        .filter_map(|(index, metric)| {
+            let best = best[index]?;
+            let names: Vec<String> = tokens.iter().zip(&measures)
+                .filter(|(_, values)| values[index].0 == Some(best))
+                .map(|(token, _)| token.tick.to_uppercase())
+                .collect();
+            Some(match names.len() {
+                1 => format!("{}: **{}**", metric.label(), names[0]),
+                _ => format!("{}: **{}** (tied)", metric.label(), names.join(", ")),
+            })
+        })
+        .collect();
+    if !leaders.is_empty() {
+        embed = embed.field("Leaders", leaders.join("\n"), false);
+    }
+    if !missing.is_empty() {
+        embed = embed.field("Not compared", missing.join("\n"), false);
+    }
+
+    Reply::new().embed(embed)
+}
+
+fn format_count(format: &NumberFormat, count: Option<u128>) -> String {
+    count.map(|count| format.count(count)).unwrap_or_else(|| "N/A".to_string())
+}
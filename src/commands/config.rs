@@ -0,0 +1,184 @@
+// src/commands/config.rs
+use crate::imports::*;
+use crate::commands::registry::{mention_list, ArgKind, ArgSpec, Command, Invocation, PREFIX};
+use crate::format::Locale;
+use crate::guild_settings::GuildSettings;
+use crate::mint_status::milestones::QuietHours;
+use crate::reply::Reply;
+use crate::state::BotState;
+use crate::theme;
+
+pub const NAME: &str = "config";
+const SETTINGS: &[&str] = &["prefix", "network", "ticker", "channels", "locale", "color", "footer", "quiet_hours"];
+const RESET: &str = "reset"; // Value that puts a setting back to the default
+const MAX_PREFIX_LEN: usize = 5;
+const MAX_TICKER_LEN: usize = 6; // KRC20 tickers have 4 to 6 characters
+const MAX_FOOTER_LEN: usize = 2048; // Discord's limit for embed footers
+
+const CONFIG_ARGS: &[ArgSpec] = &[
+    ArgSpec::optional("setting", "Setting to change", ArgKind::Choice(SETTINGS)),
+    ArgSpec::optional("value", "New value, or `reset` for the default", ArgKind::Text),
+];
+
+pub struct ConfigCommand;
+
+#[async_trait]
+impl Command for ConfigCommand {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows or changes this server's settings (Manage Server)."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        CONFIG_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+        let state = invocation.state;
+
+        let guild_id = match invocation.guild_id() {
+            Some(guild_id) => guild_id,
+            None => return Some(Reply::new().embed(theme.embed()
+                .field("Not in a server", "Settings can only be changed inside a server.", false))),
+        };
+
+        if !invocation.can_manage_guild().await {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Missing permission", "You need the Manage Server permission to view or change the settings.", false)));
+        }
+
+        let (setting, value) = match (invocation.args.get("setting"), invocation.args.get("value")) {
+            (Some(setting), Some(value)) => (setting, value),
+            _ => return Some(settings_reply(state, &state.guild_settings.get(Some(guild_id)), "Server settings")),
+        };
+
+        // Check the new value on a copy so a bad value leaves the saved settings alone
+        let saved = state.guild_settings.update(guild_id, |current| -> Result<GuildSettings, String> {
+            let mut settings = current.clone();
+            apply(state, &mut settings, setting, value)?;
+            *current = settings.clone();
+            Ok(settings)
+        }).await;
+        match saved {
+            Ok(Ok(settings)) => Some(settings_reply(state, &settings, &format!("Updated {}", setting))),
+            Ok(Err(problem)) => Some(Reply::new().embed(theme.embed()
+                .field(format!("Invalid {}", setting), problem, false))),
+            Err(e) => {
+                println!("Failed to save guild settings: {}", e);
+                Some(Reply::new().embed(theme.embed()
+                    .field("Error", "The settings could not be saved. Please try again later.", false)))
+            },
+        }
+    }
+}
+
+// Validate a value and store it in the settings, or explain what is wrong with it
+fn apply(state: &BotState, settings: &mut GuildSettings, setting: &str, value: &str) -> Result<(), String> {
+    let value = value.trim();
+    let reset = value.eq_ignore_ascii_case(RESET);
+
+    match setting.to_lowercase().as_str() {
+        "prefix" => settings.prefix = match reset {
+            true => None,
+            false if !value.is_empty() && value.chars().count() <= MAX_PREFIX_LEN && !value.contains(char::is_whitespace) => Some(value.to_string()),
+            false => return Err(format!("The prefix must be 1 to {} characters without spaces.", MAX_PREFIX_LEN)),
+        },
+        "network" => settings.network = match reset {
+            true => None,
+            false => match state.networks.get(value) {
+                Some(network) => Some(network.name.clone()),
+                None => return Err(format!("`{}` is not a configured network. Choose one of: {}", value, state.networks.names().join(", "))),
+            },
+        },
+        "ticker" => settings.ticker = match reset {
+            true => None,
+            false if !value.is_empty() && value.len() <= MAX_TICKER_LEN && value.chars().all(|c| c.is_ascii_alphanumeric()) => Some(value.to_uppercase()),
+            false => return Err(format!("`{}` is not a KRC20 ticker.", value)),
+        },
+        "channels" => settings.channels = match reset {
+            true => Vec::new(),
+            false => parse_mentions(value)?,
+        },
+        "locale" => settings.locale = match reset {
+            true => None,
+            false => match Locale::parse(value) {
+                Some(locale) => Some(locale.name.to_string()),
+                None => return Err(format!("`{}` is not a known number format. Choose one of: {}", value, Locale::NAMES.join(", "))),
+            },
+        },
+        "color" => settings.color = match reset {
+            true => None,
+            false => Some(parse_color(value).ok_or_else(|| format!("`{}` is not a colour, use hex like `#70C7BA`.", value))?),
+        },
+        "footer" => settings.footer = match reset {
+            true => None,
+            false if value.chars().count() <= MAX_FOOTER_LEN => Some(value.to_string()),
+            false => return Err(format!("The footer can be at most {} characters.", MAX_FOOTER_LEN)),
+        },
+        "quiet_hours" => settings.quiet_hours = match reset {
+            true => None,
+            false => Some(QuietHours::parse(value).ok_or_else(|| format!("`{}` is not a range of hours, use UTC hours like `22-7`, or `off`.", value))?),
+        },
+        _ => return Err(format!("Unknown setting. Choose one of: {}", SETTINGS.join(", "))),
+    }
+    Ok(())
+}
+
+// Channel, role or user mentions (or plain ids) separated by spaces or commas, e.g. `#bot-commands #trading`
+pub fn parse_mentions(value: &str) -> Result<Vec<u64>, String> {
+    value.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let id = word.trim_start_matches('<').trim_start_matches(['#', '@', '&', '!']).trim_end_matches('>');
+            id.parse::<u64>().map_err(|_| format!("`{}` is not a mention, e.g. #bot-commands or @Holders.", word))
+        })
+        .collect()
+}
+
+// Hex colour such as `#70C7BA`, `0x70C7BA` or `70C7BA`
+fn parse_color(value: &str) -> Option<u32> {
+    let hex = value.trim_start_matches('#').trim_start_matches("0x");
+    match hex.len() {
+        6 => u32::from_str_radix(hex, 16).ok(),
+        _ => None,
+    }
+}
+
+// Every setting with its current value; defaults are marked so admins see what they have changed
+fn settings_reply(state: &BotState, settings: &GuildSettings, title: &str) -> Reply {
+    let theme = theme::current();
+    let default = |value: String| format!("{} (default)", value);
+
+    let network = match settings.network.as_deref().and_then(|name| state.networks.get(name)) {
+        Some(network) => network.label.clone(),
+        None => default(state.networks.default_network().label.clone()),
+    };
+    let channels = match settings.channels.is_empty() {
+        true => default("every channel".to_string()),
+        false => mention_list(&settings.channels, "#"),
+    };
+
+    let lines = [
+        ("prefix", settings.prefix.as_ref().map(|prefix| format!("`{}`", prefix)).unwrap_or_else(|| default(format!("`{}`", PREFIX)))),
+        ("network", network),
+        ("ticker", settings.ticker.clone().unwrap_or_else(|| "not set".to_string())),
+        ("channels", channels),
+        ("locale", settings.locale.clone().unwrap_or_else(|| default(state.config.default_locale.name.to_string()))),
+        ("color", settings.color.map(|color| format!("#{:06X}", color)).unwrap_or_else(|| default(format!("#{:06X}", theme.color)))),
+        ("footer", settings.footer.clone().unwrap_or_else(|| default(theme.footer.text.clone()))),
+        ("quiet_hours", match (settings.quiet_hours, state.config.quiet_hours) {
+            (Some(quiet), _) => quiet.to_string(),
+            (None, Some(quiet)) => default(quiet.to_string()),
+            (None, None) => default("off".to_string()),
+        }),
+    ];
+    let lines: Vec<String> = lines.iter().map(|(name, value)| format!("`{}` {}", name, value)).collect();
+
+    Reply::new().embed(theme.embed()
+        .field(title, lines.join("\n"), false)
+        .field("Usage", format!("`!config [setting] [value]` changes a setting, `!config [setting] {}` puts it back to the default.", RESET), false))
+}
@@ -0,0 +1,196 @@
+// src/commands/deployments.rs
+use crate::imports::*;
+use crate::cache::current_time;
+use crate::commands::registry::{ArgKind, ArgSpec, Command, Invocation, NETWORK_FLAG};
+use crate::reply::Reply;
+use crate::state::BotState;
+use crate::storage::DeploymentFeed;
+use crate::theme;
+
+const ACTIONS: &[&str] = &["on", "off"];
+
+const DEPLOYMENTS_ARGS: &[ArgSpec] = &[
+    ArgSpec::optional("action", "on announces new tokens in this channel, off stops it", ArgKind::Choice(ACTIONS)),
+    ArgSpec::flag("min-supply", "Smallest max supply to announce, in whole tokens", ArgKind::Text),
+    ArgSpec::flag("max-pre", "Largest pre-allocation to announce, in % of the max supply", ArgKind::Text),
+    NETWORK_FLAG,
+];
+
+pub struct DeploymentsCommand;
+
+#[async_trait]
+impl Command for DeploymentsCommand {
+    fn name(&self) -> &'static str {
+        "deployments"
+    }
+
+    fn description(&self) -> &'static str {
+        "Announces newly deployed tokens in this channel (Manage Server)."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        DEPLOYMENTS_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+        let state = invocation.state;
+        let args = &invocation.args;
+
+        let guild_id = match invocation.guild_id() {
+            Some(guild_id) => guild_id,
+            None => return Some(Reply::new().embed(theme.embed()
+                .field("Not in a server", "Deployments can only be announced in a server channel.", false))),
+        };
+
+        let action = match args.get("action") {
+            Some(action) => action.to_lowercase(),
+            None => return Some(feeds_reply(state, invocation.channel_id().get(), "Deployment announcements").await),
+        };
+
+        if !invocation.can_manage_guild().await {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Missing permission", "You need the Manage Server permission to change the announcements of a channel.", false)));
+        }
+
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+        let channel_id = invocation.channel_id().get();
+
+        let saved = match action.as_str() {
+            "on" => {
+                let min_supply = match args.get("min-supply").map(parse_supply) {
+                    Some(Some(min_supply)) => min_supply,
+                    Some(None) => return Some(invalid_reply("min-supply", "The minimum supply must be a whole number of tokens, e.g. `1000000` or `1M`.")),
+                    None => 0,
+                };
+                let max_pre = match args.get("max-pre").map(parse_percent) {
+                    Some(Some(max_pre)) => Some(max_pre),
+                    Some(None) => return Some(invalid_reply("max-pre", "The maximum pre-allocation must be a percentage from 0 to 100, e.g. `5` or `2.5%`.")),
+                    None => None,
+                };
+                let feed = DeploymentFeed { channel_id, guild_id: guild_id.get(), network: network.name.clone(), min_supply, max_pre };
+                state.storage.add_deployment_feed(feed, current_time()).await
+            },
+            "off" => match state.storage.remove_deployment_feed(channel_id, &network.name).await {
+                Ok(false) => return Some(Reply::new().embed(theme.embed()
+                    .field("Not set up", format!("No announcements are set up in this channel on {}.", network.label), false))),
+                removed => removed.map(|_| ()),
+            },
+            _ => return Some(Reply::new().embed(theme.embed()
+                .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!deployments [on|off] [--min-supply tokens] [--max-pre percent]`!", false))),
+        };
+
+        match saved {
+            Ok(()) => Some(feeds_reply(state, channel_id, "Updated the deployment announcements").await),
+            Err(e) => {
+                println!("Failed to save the deployment feed: {}", e);
+                Some(Reply::new().embed(theme.embed()
+                    .field("Error", "The announcements could not be saved. Please try again later.", false)))
+            },
+        }
+    }
+}
+
+// Whole tokens, with an optional K, M or B suffix
+fn parse_supply(value: &str) -> Option<u128> {
+    let value = value.trim().to_uppercase().replace([',', '_'], "");
+    let (number, power) = match value.chars().last()? {
+        'K' => (&value[..value.len() - 1], 3),
+        'M' => (&value[..value.len() - 1], 6),
+        'B' => (&value[..value.len() - 1], 9),
+        _ => (value.as_str(), 0),
+    };
+    number.parse::<u128>().ok()?.checked_mul(10u128.pow(power))
+}
+
+// A percentage in hundredths, e.g. `2.5%` is 250
+fn parse_percent(value: &str) -> Option<u32> {
+    let percent: f64 = value.trim().trim_end_matches('%').trim_end().parse().ok()?;
+    (0.0..=100.0).contains(&percent).then(|| (percent * 100.0).round() as u32)
+}
+
+// The feeds of a channel with their filters
+async fn feeds_reply(state: &BotState, channel_id: u64, title: &str) -> Reply {
+    let lines = match state.storage.deployment_feeds(Some(channel_id)).await {
+        Ok(feeds) if feeds.is_empty() => "New deployments are not announced in this channel.".to_string(),
+        Ok(feeds) => feeds.iter()
+            .map(|feed| {
+                let network = state.networks.get(&feed.network).map(|network| network.label.as_str()).unwrap_or(&feed.network);
+                let mut filters = Vec::new();
+                if feed.min_supply > 0 {
+                    filters.push(format!("max supply at least {}", feed.min_supply));
+                }
+                if let Some(max_pre) = feed.max_pre {
+                    filters.push(format!("pre-allocation at most {}.{:02}%", max_pre / 100, max_pre % 100));
+                }
+                match filters.is_empty() {
+                    true => format!("{}: every new token", network),
+                    false => format!("{}: {}", network, filters.join(", ")),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(e) => {
+            println!("Failed to list the deployment feeds: {}", e);
+            "The announcements could not be loaded. Please try again later.".to_string()
+        },
+    };
+
+    Reply::new().embed(theme::current().embed()
+        .field(title, lines, false)
+        .field("Usage", "`!deployments on [--min-supply tokens] [--max-pre percent] [--net network]` announces new tokens here, `!deployments off` stops it.", false))
+}
+
+fn invalid_reply(flag: &str, problem: &str) -> Reply {
+    Reply::new().embed(theme::current().embed()
+        .field(format!("Invalid --{}", flag), problem, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supplies_take_suffixes_and_separators() {
+        assert_eq!(parse_supply("1000"), Some(1_000));
+        assert_eq!(parse_supply(" 5k "), Some(5_000));
+        assert_eq!(parse_supply("21M"), Some(21_000_000));
+        assert_eq!(parse_supply("2B"), Some(2_000_000_000));
+        assert_eq!(parse_supply("1,000,000"), Some(1_000_000));
+        assert_eq!(parse_supply("1_000_000"), Some(1_000_000));
+    }
+
+    #[test]
+    fn supplies_reject_fractions_and_overflow() {
+        assert_eq!(parse_supply(""), None);
+        assert_eq!(parse_supply("M"), None);
+        assert_eq!(parse_supply("1.5M"), None);
+        assert_eq!(parse_supply("-5"), None);
+        assert_eq!(parse_supply("5T"), None);
+        assert_eq!(parse_supply("340282366920938463463374607431768211455"), Some(u128::MAX));
+        assert_eq!(parse_supply("340282366920938463463374607431768211455K"), None);
+        assert_eq!(parse_supply("340282366920938463463374607431768211456"), None);
+    }
+
+    #[test]
+    fn percents_are_kept_in_hundredths() {
+        assert_eq!(parse_percent("5"), Some(500));
+        assert_eq!(parse_percent("2.5%"), Some(250));
+        assert_eq!(parse_percent(" 0.01 % "), Some(1));
+        assert_eq!(parse_percent("0"), Some(0));
+        assert_eq!(parse_percent("100"), Some(10_000));
+    }
+
+    #[test]
+    fn percents_outside_the_range_are_rejected() {
+        assert_eq!(parse_percent("100.01"), None);
+        assert_eq!(parse_percent("-1"), None);
+        assert_eq!(parse_percent("NaN"), None);
+        assert_eq!(parse_percent("inf"), None);
+        assert_eq!(parse_percent("five"), None);
+        assert_eq!(parse_percent(""), None);
+    }
+}
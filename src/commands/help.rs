@@ -1,84 +1,50 @@
+// src/commands/help.rs
 use crate::imports::*;
-use serde_json::Value;
-use std::fs;
-use serenity::builder::CreateEmbedFooter;
-use rand::distributions::{Distribution, WeightedIndex};
-use rand::thread_rng;
+use crate::commands::registry::{Command, Invocation, Registry, PREFIX};
+use crate::reply::Reply;
+use crate::theme;
 
-pub async fn handle_help_command(ctx: &Context, msg: &Message) {
-    // Load the JSON template
-    let template_content = match fs::read_to_string("message_template.json") {
-        Ok(content) => content,
-        Err(e) => {
-            println!("Failed to read message template: {:?}", e);
-            return;
-        }
-    };
-
-    let template: Value = match serde_json::from_str(&template_content) {
-        Ok(template) => template,
-        Err(e) => {
-            println!("Failed to parse message template: {:?}", e);
-            return;
-        }
-    };
+pub struct HelpCommand;
 
-    // Ensure required fields are present in the template
-    let color = template["color"].as_u64().expect("Color not found in message template") as u32;
-    let background_images = template["background_images"].as_array().expect("Background images not found in message template");
-    let background_image_url = select_random_banner(background_images);
-    let author_name = template["author"]["name"].as_str().expect("Author name not found in message template");
-    let author_icon_url = template["author"]["icon_url"].as_str().expect("Author icon URL not found in message template");
+#[async_trait]
+impl Command for HelpCommand {
+    fn name(&self) -> &'static str {
+        "helpme"
+    }
 
-    // Load the help content from the JSON file
-    let help_content = match fs::read_to_string("help_content.json") {
-        Ok(content) => content,
-        Err(e) => {
-            println!("Failed to read help content: {:?}", e);
-            return;
-        }
-    };
+    fn description(&self) -> &'static str {
+        "Displays this help menu."
+    }
 
-    let help_json: Value = match serde_json::from_str(&help_content) {
-        Ok(json) => json,
-        Err(e) => {
-            println!("Failed to parse help content: {:?}", e);
-            return;
-        }
-    };
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        // Show the commands with the prefix this server uses
+        let prefix = invocation.state.guild_settings.get(invocation.guild_id()).prefix;
+        Some(help_reply(&invocation.state.registry, prefix.as_deref().unwrap_or(PREFIX)))
+    }
+}
 
-    let commands = help_json["commands"].as_array().expect("Commands not found in help content");
+// Build the help menu from the registered commands so it always matches what is wired up
+fn help_reply(registry: &Registry, prefix: &str) -> Reply {
+    let theme = theme::current();
 
-    let mut embed = CreateEmbed::new()
-        .color(color)
-        .image(background_image_url)
-        .author(CreateEmbedAuthor::new(author_name).icon_url(author_icon_url))
-        .footer(CreateEmbedFooter::new("x.com/coinchimpx"));
+    let mut embed = theme.embed();
 
     // Add content and footer
-    let content = format!("**# Help Menu**");
+    let content = "**# Help Menu**";
 
     // Add the commands to the embed
-    for command in commands {
-        let name = command["name"].as_str().unwrap_or("Unknown Command");
-        let description = command["description"].as_str().unwrap_or("No description available");
-        embed = embed.field(name, description, false);
+    for command in registry.commands() {
+        let mut description = command.description().to_string();
+        if !command.aliases().is_empty() {
+            let aliases: Vec<String> = command.aliases().iter().map(|alias| format!("`{}{}`", prefix, alias)).collect();
+            description.push_str(&format!("\nAliases: {}", aliases.join(", ")));
+        }
+        let usage = command.usage().replacen(PREFIX, prefix, 1);
+        embed = embed.field(usage, description, false);
     }
 
-    let payload = CreateMessage::new()
+    Reply::new()
         .content(content)
-        .embed(embed);
-
-    if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), payload).await {
-        println!("Error sending message: {:?}", why);
-    }
+        .embed(embed)
 }
 
-// Helper function to select a random background image based on weight
-fn select_random_banner(banners: &Vec<Value>) -> &str {
-    let weights: Vec<_> = banners.iter().map(|b| b["weight"].as_u64().unwrap_or(1) as u32).collect();
-    let dist = WeightedIndex::new(&weights).unwrap();
-    let mut rng = thread_rng();
-    let index = dist.sample(&mut rng);
-    banners[index]["url"].as_str().unwrap()
-}
@@ -1,106 +1,85 @@
 // src/commands/holder.rs
 use crate::imports::*;
+use crate::address::{Address, AddressError};
 use crate::holder_status::datatweaks;
-use serde_json::Value;
-use std::fs;
-use rand::distributions::{Distribution, WeightedIndex};
-use rand::thread_rng;
+use crate::commands::registry::{ArgKind, ArgSpec, Command, Invocation, EXACT_FLAG, NETWORK_FLAG};
+use crate::commands::api_error::api_error_reply;
+use crate::cache::Cache;
+use crate::format::NumberFormat;
+use crate::networks::{Network, Networks};
+use crate::reply::Reply;
+use crate::theme;
 
-// Helper function to select a random background image based on weight
-fn select_random_banner(banners: &Vec<Value>) -> &str {
-    let weights: Vec<_> = banners.iter().map(|b| b["weight"].as_u64().unwrap_or(1) as u32).collect();
-    let dist = WeightedIndex::new(&weights).unwrap();
-    let mut rng = thread_rng();
-    let index = dist.sample(&mut rng);
-    banners[index]["url"].as_str().unwrap()
-}
 
-pub async fn handle_holder_command(ctx: &Context, msg: &Message, message_parts: &mut std::str::SplitWhitespace<'_>, api_base_url: &str) {
-    let message_word_count = msg.content.split_whitespace().count();
+const ADDRESS_ARGS: &[ArgSpec] = &[ArgSpec::required("wallet-address", "Kaspa wallet address", ArgKind::Address), NETWORK_FLAG, EXACT_FLAG];
 
-    // Load the JSON template
-    let template_content = match fs::read_to_string("message_template.json") {
-        Ok(content) => content,
-        Err(e) => {
-            println!("Failed to read message template: {:?}", e);
-            return;
-        }
-    };
+pub struct HolderCommand;
 
-    let template: Value = match serde_json::from_str(&template_content) {
-        Ok(template) => template,
-        Err(e) => {
-            println!("Failed to parse message template: {:?}", e);
-            return;
-        }
-    };
+#[async_trait]
+impl Command for HolderCommand {
+    fn name(&self) -> &'static str {
+        "tokenbalance"
+    }
 
-    // Ensure required fields are present in the template
-    let color = match template["color"].as_u64() {
-        Some(color) => color as u32,
-        None => {
-            println!("Color not found in message template");
-            return;
-        }
-    };
+    fn aliases(&self) -> &'static [&'static str] {
+        &["balance"]
+    }
 
-    let background_images = match template["background_images"].as_array() {
-        Some(images) => images,
-        None => {
-            println!("Background images not found in message template");
-            return;
-        }
-    };
+    fn description(&self) -> &'static str {
+        "Displays the balance of krc20 projects held by this wallet address."
+    }
 
-    let background_image_url = select_random_banner(background_images);
+    fn args(&self) -> &'static [ArgSpec] {
+        ADDRESS_ARGS
+    }
 
-    let author_name = match template["author"]["name"].as_str() {
-        Some(name) => name,
-        None => {
-            println!("Author name not found in message template");
-            return;
-        }
-    };
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+        holder_reply(invocation.state.cache.as_ref(), &invocation.state.networks, network, &invocation.number_format(), invocation.args.get("wallet-address")).await
+    }
+}
 
-    let author_icon_url = match template["author"]["icon_url"].as_str() {
-        Some(url) => url,
-        None => {
-            println!("Author icon URL not found in message template");
-            return;
-        }
-    };
+// Build the wallet balance reply for an address, shared by the prefix and slash commands
+pub async fn holder_reply(cache: &dyn Cache, networks: &Networks, network: &Network, format: &NumberFormat, address: Option<&str>) -> Option<Reply> {
+    let theme = theme::current();
 
-    if message_word_count == 2 {
-        let address = message_parts.next().unwrap_or("");
-        
-        match datatweaks::fetch_holder_data(api_base_url, address).await {
-            Ok(data) => {
-                let formatted_message = datatweaks::format_holder_data(data, address).await;
-                if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), formatted_message).await {
-                    println!("Error sending message: {:?}", why);
-                }
-            },
+    if let Some(input) = address {
+        // Check the address locally first, a typo or a mainnet address on testnet never reaches the API
+        let address = match Address::parse(input, &network.address_prefix) {
+            Ok(address) => address.to_string(),
+            Err(e) => return Some(invalid_address_reply(networks, network, input, &e)),
+        };
+        match datatweaks::fetch_holder_data(cache, network, &address).await {
+            Ok(data) => Some(datatweaks::format_holder_data(network, format, data, &address).await),
             Err(e) => {
-                let error_message = CreateMessage::new().embed(CreateEmbed::new()
-                    .color(color)
-                    .image(background_image_url)
-                    .field("Error", "Failed to fetch holder data. Please check the address and try again.", false)
-                    .author(CreateEmbedAuthor::new(author_name).icon_url(author_icon_url)));
-                if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), error_message).await {
-                    println!("Error sending message: {:?}", why);
-                }
                 println!("Failed to fetch holder data: {}", e);
+                Some(api_error_reply(&e, "!tokenbalance [wallet-address]", network))
             }
         }
     } else {
         // Use the template to create the parameter error message
-        let parameter_error = CreateMessage::new().embed(CreateEmbed::new()
-            .color(color)
-            .image(background_image_url)
-            .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!tokenbalance [wallet-address]`!", false)
-            .author(CreateEmbedAuthor::new(author_name).icon_url(author_icon_url)));
-        if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), parameter_error).await {
-            println!("Error sending message: {:?}", why);
+        Some(Reply::new().embed(theme.embed()
+            .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!tokenbalance [wallet-address]`!", false)))
+    }
+}
+
+// Explain why an address was refused; for another network's address point at the networks it belongs to
+fn invalid_address_reply(networks: &Networks, network: &Network, input: &str, error: &AddressError) -> Reply {
+    let mut embed = theme::current().embed()
+        .field("Invalid address", format!("`{}` cannot be used on {}: {}.", input.trim(), network.label, error), false);
+
+    if let AddressError::WrongNetwork { found, .. } = error {
+        let matching: Vec<String> = networks.names().into_iter()
+            .filter_map(|name| networks.get(name))
+            .filter(|candidate| &candidate.address_prefix == found)
+            .map(|candidate| format!("`--net {}`", candidate.name))
+            .collect();
+        if !matching.is_empty() {
+            embed = embed.field("Try", format!("Add {} to look the address up on its own network.", matching.join(" or ")), false);
         }
     }
+    Reply::new().embed(embed)
 }
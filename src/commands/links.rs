@@ -1,86 +1,74 @@
 // src/commands/links.rs
 use crate::imports::*;
-use serde_json::Value;
-use std::fs;
-use rand::distributions::{Distribution, WeightedIndex};
-use rand::thread_rng;
-use serenity::builder::CreateEmbedFooter; // Add this import
+use crate::commands::registry::{Command, Invocation};
+use crate::reply::Reply;
+use crate::hot_reload::{ConfigError, ConfigFile, Reloadable};
+use crate::theme;
 
-// Helper function to select a random background image based on weight
-fn select_random_banner(banners: &Vec<Value>) -> &str {
-    let weights: Vec<_> = banners.iter().map(|b| b["weight"].as_u64().unwrap_or(1) as u32).collect();
-    let dist = WeightedIndex::new(&weights).unwrap();
-    let mut rng = thread_rng();
-    let index = dist.sample(&mut rng);
-    banners[index]["url"].as_str().unwrap()
+// Official links, reloaded when nacho_links.json changes
+pub static LINKS: Reloadable<LinkList> = Reloadable::new();
+
+#[derive(Debug, Deserialize)]
+pub struct Link {
+    pub name: String,
+    pub url: String,
 }
 
-pub async fn handle_links_command(ctx: &Context, msg: &Message) {
-    // Load the JSON file
-    let links_content = match fs::read_to_string("nacho_links.json") {
-        Ok(content) => content,
-        Err(e) => {
-            println!("Failed to read links file: {:?}", e);
-            return;
-        }
-    };
+// Typed contents of nacho_links.json
+#[derive(Debug, Deserialize)]
+pub struct LinkList {
+    pub links: Vec<Link>,
+}
 
-    let links: Value = match serde_json::from_str(&links_content) {
-        Ok(links) => links,
-        Err(e) => {
-            println!("Failed to parse links file: {:?}", e);
-            return;
-        }
-    };
+impl ConfigFile for LinkList {
+    const PATH: &'static str = "nacho_links.json";
 
-    // Load the JSON template
-    let template_content = match fs::read_to_string("message_template.json") {
-        Ok(content) => content,
-        Err(e) => {
-            println!("Failed to read message template: {:?}", e);
-            return;
+    fn parse(content: &str) -> Result<Self, ConfigError> {
+        let list: LinkList = serde_json::from_str(content)?;
+        for link in &list.links {
+            if link.name.trim().is_empty() {
+                return Err(ConfigError::Invalid(format!("link to {} has no name", link.url)));
+            }
+            if !link.url.starts_with("https://") && !link.url.starts_with("http://") {
+                return Err(ConfigError::Invalid(format!("link {} has an invalid url: {}", link.name, link.url)));
+            }
         }
-    };
+        Ok(list)
+    }
+}
 
-    let template: Value = match serde_json::from_str(&template_content) {
-        Ok(template) => template,
-        Err(e) => {
-            println!("Failed to parse message template: {:?}", e);
-            return;
-        }
-    };
+pub struct LinksCommand;
 
-    // Ensure required fields are present in the template
-    let color = template["color"].as_u64().expect("Color not found in message template") as u32;
-    let background_images = template["background_images"].as_array().expect("Background images not found in message template");
-    let background_image_url = select_random_banner(background_images);
-    let author_name = template["author"]["name"].as_str().expect("Author name not found in message template");
-    let author_icon_url = template["author"]["icon_url"].as_str().expect("Author icon URL not found in message template");
+#[async_trait]
+impl Command for LinksCommand {
+    fn name(&self) -> &'static str {
+        "links"
+    }
 
-    // Ensure required fields are present in the links
-    let links_array = links["links"].as_array().expect("Links array not found in links file");
+    fn description(&self) -> &'static str {
+        "Displays the official links."
+    }
+
+    async fn execute(&self, _invocation: &Invocation<'_>) -> Option<Reply> {
+        Some(links_reply())
+    }
+}
+
+fn links_reply() -> Reply {
+    let theme = theme::current();
+    let links = LINKS.current();
 
     // Create the content for the message
-    let content = format!("**# Official Links**");
+    let content = "**# Official Links**";
 
     // Create the message payload with an embedded message
-    let mut embed = CreateEmbed::new()
-        .color(color)
-        .image(background_image_url)
-        .author(CreateEmbedAuthor::new(author_name).icon_url(author_icon_url))
-        .footer(CreateEmbedFooter::new("x.com/coinchimpx"));
+    let mut embed = theme.embed();
 
-    for link in links_array {
-        let name = link["name"].as_str().expect("Link name not found");
-        let url = link["url"].as_str().expect("Link URL not found");
-        embed = embed.field(name, url, false);
+    for link in &links.links {
+        embed = embed.field(&link.name, &link.url, false);
     }
 
-    let payload = CreateMessage::new()
-        .content(content) // Add this line
-        .embed(embed);
-
-    if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), payload).await {
-        println!("Error sending message: {:?}", why);
-    }
+    Reply::new()
+        .content(content)
+        .embed(embed)
 }
@@ -0,0 +1,44 @@
+// src/commands/locale.rs
+use crate::imports::*;
+use crate::amount::TokenAmount;
+use crate::commands::registry::{Command, Invocation};
+use crate::format::{Field, Locale, NumberFormat};
+use crate::reply::Reply;
+use crate::theme;
+
+const SAMPLE: u128 = 1_234_567_891; // 12,345.67891 with 5 decimals, shown next to each locale
+
+pub struct LocaleCommand;
+
+#[async_trait]
+impl Command for LocaleCommand {
+    fn name(&self) -> &'static str {
+        "locale"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows the number formats and the one this server uses."
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        Some(list_locales_reply(invocation))
+    }
+}
+
+// List the locales with a sample number and mark the one this server uses
+fn list_locales_reply(invocation: &Invocation<'_>) -> Reply {
+    let current = invocation.number_format().locale;
+    let sample = TokenAmount::new(SAMPLE, 5);
+
+    let lines: Vec<String> = Locale::NAMES.iter()
+        .filter_map(|name| Locale::parse(name))
+        .map(|locale| {
+            let marker = if locale == current { " ← current" } else { "" };
+            format!("`{}` {}{}", locale.name, NumberFormat::new(locale, true).amount(&sample, Field::Supply), marker)
+        })
+        .collect();
+
+    Reply::new().embed(theme::current().embed()
+        .field("Number formats", lines.join("\n"), false)
+        .field("Usage", "Set the server's number format with `!config locale [locale]`. Add `--exact` to a command to see every digit.", false))
+}
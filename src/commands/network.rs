@@ -0,0 +1,43 @@
+// src/commands/network.rs
+use crate::imports::*;
+use crate::commands::registry::{Command, Invocation};
+use crate::reply::Reply;
+use crate::theme;
+
+pub struct NetworkCommand;
+
+#[async_trait]
+impl Command for NetworkCommand {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows the networks and the one this server uses."
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        Some(list_networks_reply(invocation))
+    }
+}
+
+// List the configured networks and mark the one this server uses
+fn list_networks_reply(invocation: &Invocation<'_>) -> Reply {
+    let networks = &invocation.state.networks;
+    let current = match invocation.network() {
+        Ok(network) => network,
+        Err(_) => networks.default_network(), // The saved network was removed from networks.json
+    };
+
+    let lines: Vec<String> = networks.names().into_iter()
+        .filter_map(|name| networks.get(name))
+        .map(|network| {
+            let marker = if network.name == current.name { " ← current" } else { "" };
+            format!("`{}` {}{}", network.name, network.label, marker)
+        })
+        .collect();
+
+    Reply::new().embed(theme::current().embed()
+        .field("Networks", lines.join("\n"), false)
+        .field("Usage", "Add `--net [network]` to a command, or set the server default with `!config network [network]`.", false))
+}
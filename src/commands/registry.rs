@@ -0,0 +1,548 @@
+// src/commands/registry.rs
+use crate::imports::*;
+use crate::commands::{chart, compare, config, deployments, donate, help, holder, links, locale, network, rules, status, subscribe, topholders, watch};
+use crate::format::{Locale, NumberFormat};
+use crate::guild_settings::GuildSettings;
+use crate::cache::current_time;
+use crate::networks::Network;
+use crate::rate_limit::Cooldown;
+use crate::reply::Reply;
+use crate::state::BotState;
+use crate::theme;
+use serenity::model::application::CommandDataOptionValue;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use serenity::model::permissions::Permissions;
+use std::collections::HashMap;
+use std::time::Instant;
+
+pub const PREFIX: &str = "!"; // Prefix for message commands, e.g. `!tokeninfo`
+
+// The kind of value an argument takes, used for slash command options and autocomplete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Ticker,  // A KRC20 ticker, autocompleted from the cached tokens
+    Address, // A Kaspa wallet address
+    Network, // A network name from networks.json, autocompleted
+    Choice(&'static [&'static str]), // One of a fixed list, offered as slash command choices
+    Switch,  // A flag without a value, e.g. `--exact`; a true/false option as a slash command
+    Text,    // Free text; as the last positional argument it takes the rest of the message
+    Command, // The name of one of the bot's commands, autocompleted
+}
+
+impl ArgKind {
+    // Placeholder shown after a flag in the usage line
+    fn placeholder(&self) -> &'static str {
+        match self {
+            ArgKind::Ticker => "ticker",
+            ArgKind::Address => "address",
+            ArgKind::Network => "network",
+            ArgKind::Choice(_) => "value",
+            ArgKind::Switch => "",
+            ArgKind::Text => "text",
+            ArgKind::Command => "command",
+        }
+    }
+}
+
+// Declares one argument of a command: positional (`!tokeninfo NACHO`) or a named flag (`--net mainnet`)
+#[derive(Debug, Clone)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: ArgKind,
+    pub required: bool,
+    pub flag: bool,
+}
+
+impl ArgSpec {
+    pub const fn required(name: &'static str, description: &'static str, kind: ArgKind) -> Self {
+        ArgSpec { name, description, kind, required: true, flag: false }
+    }
+
+    pub const fn optional(name: &'static str, description: &'static str, kind: ArgKind) -> Self {
+        ArgSpec { name, description, kind, required: false, flag: false }
+    }
+
+    pub const fn flag(name: &'static str, description: &'static str, kind: ArgKind) -> Self {
+        ArgSpec { name, description, kind, required: false, flag: true }
+    }
+}
+
+// The `--net` flag shared by every command that reads token data
+pub const NETWORK_FLAG: ArgSpec = ArgSpec::flag("net", "Network to query, e.g. mainnet", ArgKind::Network);
+
+// The `--exact` switch that shows every digit instead of compact numbers
+pub const EXACT_FLAG: ArgSpec = ArgSpec::flag("exact", "Show exact amounts instead of 1.23M", ArgKind::Switch);
+
+// Argument values by name, filled from either the message words or the slash command options
+#[derive(Debug, Default)]
+pub struct CommandArgs {
+    values: HashMap<String, String>,
+}
+
+impl CommandArgs {
+    // Map `--flag value` pairs onto the flags and the remaining words onto the positional arguments in order.
+    // A trailing Text argument takes all remaining words; otherwise too many words leave every positional
+    // argument unset so the command reports its usage.
+    pub fn from_words<'a>(schema: &[ArgSpec], mut words: impl Iterator<Item = &'a str>) -> Self {
+        let mut values = HashMap::new();
+        let mut positional = Vec::new();
+
+        while let Some(word) = words.next() {
+            let flag = word.strip_prefix("--")
+                .and_then(|name| schema.iter().find(|spec| spec.flag && spec.name == name));
+            if let Some(spec) = flag.filter(|spec| spec.kind == ArgKind::Switch) {
+                values.insert(spec.name.to_string(), true.to_string());
+                continue;
+            }
+            match (flag, flag.and_then(|_| words.next())) {
+                (Some(spec), Some(value)) => {
+                    values.insert(spec.name.to_string(), value.to_string());
+                },
+                (Some(_), None) => {}, // A trailing flag without a value is ignored
+                (None, _) => positional.push(word),
+            }
+        }
+
+        let positional_specs: Vec<&ArgSpec> = schema.iter().filter(|spec| !spec.flag).collect();
+        if let Some(last) = positional_specs.last().filter(|spec| spec.kind == ArgKind::Text) {
+            if positional.len() > positional_specs.len() {
+                let rest = positional.split_off(positional_specs.len() - 1).join(" ");
+                values.insert(last.name.to_string(), rest);
+            }
+        }
+        if positional.len() <= positional_specs.len() {
+            for (spec, word) in positional_specs.into_iter().zip(positional) {
+                values.insert(spec.name.to_string(), word.to_string());
+            }
+        }
+
+        CommandArgs { values }
+    }
+
+    // Read the options of a slash command
+    pub fn from_interaction(command: &CommandInteraction) -> Self {
+        let values = command.data.options.iter()
+            .filter_map(|option| {
+                let value = match &option.value {
+                    CommandDataOptionValue::String(value) => value.clone(),
+                    CommandDataOptionValue::Boolean(value) => value.to_string(),
+                    CommandDataOptionValue::Integer(value) => value.to_string(),
+                    _ => return None,
+                };
+                Some((option.name.clone(), value))
+            })
+            .collect();
+
+        CommandArgs { values }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|value| value.as_str())
+    }
+
+    // Whether a switch such as `--exact` was given
+    pub fn switch(&self, name: &str) -> bool {
+        self.get(name) == Some("true")
+    }
+}
+
+// Everything a command needs to run, regardless of whether it came from a message or a slash command
+pub struct Invocation<'a> {
+    pub ctx: &'a Context,
+    pub source: Source<'a>,
+    pub state: &'a BotState,
+    pub args: CommandArgs,
+}
+
+// Where a command came from
+#[derive(Clone, Copy)]
+pub enum Source<'a> {
+    Message(&'a Message),
+    Interaction(&'a CommandInteraction),
+}
+
+impl Invocation<'_> {
+    pub fn guild_id(&self) -> Option<GuildId> {
+        match self.source {
+            Source::Message(msg) => msg.guild_id,
+            Source::Interaction(command) => command.guild_id,
+        }
+    }
+
+    pub fn user_id(&self) -> UserId {
+        match self.source {
+            Source::Message(msg) => msg.author.id,
+            Source::Interaction(command) => command.user.id,
+        }
+    }
+
+    pub fn channel_id(&self) -> ChannelId {
+        match self.source {
+            Source::Message(msg) => msg.channel_id,
+            Source::Interaction(command) => command.channel_id,
+        }
+    }
+
+    // Whether the caller may change the guild's settings (Manage Guild permission)
+    pub async fn can_manage_guild(&self) -> bool {
+        let permissions = match self.source {
+            // Discord sends the member's resolved permissions with every interaction
+            Source::Interaction(command) => command.member.as_ref().and_then(|member| member.permissions),
+            Source::Message(msg) => {
+                let member = match msg.member(self.ctx).await {
+                    Ok(member) => member,
+                    Err(_) => return false, // Direct messages have no guild to manage
+                };
+                msg.guild(&self.ctx.cache).map(|guild| guild.member_permissions(&member))
+            },
+        };
+        permissions.is_some_and(|permissions| permissions.contains(Permissions::MANAGE_GUILD))
+    }
+
+    // Network for this command: the `--net` flag, then the guild default, then the bot default
+    pub fn network(&self) -> Result<&Network, Reply> {
+        let networks = &self.state.networks;
+        let requested = self.args.get(NETWORK_FLAG.name)
+            .map(str::to_string)
+            .or_else(|| self.state.guild_settings.get(self.guild_id()).network);
+
+        match requested {
+            Some(name) => networks.get(&name).ok_or_else(|| unknown_network_reply(&name, &networks.names())),
+            None => Ok(networks.default_network()),
+        }
+    }
+
+    // The ticker argument, or the guild's default ticker when it was left out
+    pub fn ticker(&self) -> Option<String> {
+        self.args.get("ticker")
+            .map(str::to_string)
+            .or_else(|| self.state.guild_settings.get(self.guild_id()).ticker)
+    }
+
+    // Numbers in this guild's locale, exact when the command has `--exact`
+    pub fn number_format(&self) -> NumberFormat {
+        number_format(self.state, self.guild_id(), self.args.switch(EXACT_FLAG.name))
+    }
+}
+
+// Numbers in a guild's locale, falling back to the bot's default locale
+pub fn number_format(state: &BotState, guild_id: Option<GuildId>, exact: bool) -> NumberFormat {
+    let locale = state.guild_settings.get(guild_id).locale
+        .and_then(|name| Locale::parse(&name))
+        .unwrap_or(state.config.default_locale);
+    NumberFormat::new(locale, exact)
+}
+
+// Why a command may not be used here by this member, telling them where or who can; None when it may run.
+// The server's channel list applies to every command, a command rule only to its own command.
+// `!config` and `!rules` always work so admins cannot lock themselves out.
+pub fn access_denial(command: &dyn Command, settings: &GuildSettings, channel_id: ChannelId, user_id: UserId, roles: &[RoleId]) -> Option<String> {
+    if [config::NAME, rules::NAME].contains(&command.name()) {
+        return None;
+    }
+    if !settings.allows_channel(channel_id) {
+        return Some(format!("Commands can only be used in {}.", mention_list(&settings.channels, "#")));
+    }
+
+    let rule = settings.rules.get(command.name())?;
+    if !rule.allows_member(user_id, roles) {
+        let mut allowed = Vec::new();
+        if !rule.roles.is_empty() {
+            allowed.push(format!("members with {}", mention_list(&rule.roles, "@&")));
+        }
+        if !rule.users.is_empty() {
+            allowed.push(mention_list(&rule.users, "@"));
+        }
+        return Some(format!("`{}` can only be used by {}.", command.name(), allowed.join(" or ")));
+    }
+    if !rule.allows_channel(channel_id) {
+        return Some(format!("`{}` can only be used in {}.", command.name(), mention_list(&rule.channels, "#")));
+    }
+    None
+}
+
+// Friendly notice with a countdown to when the command can be used again
+pub fn cooldown_reply(command: &dyn Command, cooldown: Cooldown) -> Reply {
+    let retry_at = current_time().saturating_add(cooldown.retry_after.as_secs().saturating_add(1));
+    Reply::new().embed(theme::current().embed()
+        .field("Slow down", format!("`{}` is cooling down. You can use it again <t:{}:R>.", command.name(), retry_at), false))
+}
+
+// Discord mentions for a list of ids, e.g. `<#1>, <#2>` for channels with the `#` sigil
+pub fn mention_list(ids: &[u64], sigil: &str) -> String {
+    ids.iter().map(|id| format!("<{}{}>", sigil, id)).collect::<Vec<_>>().join(", ")
+}
+
+// Tell the user which networks exist when they ask for one that does not
+pub fn unknown_network_reply(name: &str, known: &[&str]) -> Reply {
+    Reply::new().embed(theme::current().embed()
+        .field("Unknown network", format!("`{}` is not a configured network. Choose one of: {}", name, known.join(", ")), false))
+}
+
+#[async_trait]
+pub trait Command: Send + Sync {
+    // Name used for both `!name` and `/name`
+    fn name(&self) -> &'static str;
+
+    // Extra names accepted for the prefix command
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn description(&self) -> &'static str;
+
+    fn args(&self) -> &'static [ArgSpec] {
+        &[]
+    }
+
+    // Usage line shown in the help menu, e.g. `!tokeninfo [ticker] [--net network]`
+    fn usage(&self) -> String {
+        let mut usage = format!("{}{}", PREFIX, self.name());
+        for arg in self.args() {
+            if arg.kind == ArgKind::Switch {
+                usage.push_str(&format!(" [--{}]", arg.name));
+            } else if arg.flag {
+                usage.push_str(&format!(" [--{} {}]", arg.name, arg.kind.placeholder()));
+            } else {
+                usage.push_str(&format!(" [{}]", arg.name));
+            }
+        }
+        usage
+    }
+
+    // Run the command, returning None when there is nothing to send
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply>;
+
+    // Handle a press on one of the buttons this command attached. The custom id is
+    // `name:part:part...` and `parts` holds everything after the name.
+    async fn handle_component(&self, _ctx: &Context, _component: &ComponentInteraction, _state: &BotState, _parts: &[&str]) {}
+}
+
+// Every command the bot understands; dispatch and help are both driven from this list
+pub struct Registry {
+    commands: Vec<Box<dyn Command>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            commands: vec![
+                Box::new(status::StatusCommand),
+                Box::new(chart::ChartCommand),
+                Box::new(holder::HolderCommand),
+                Box::new(topholders::TopHoldersCommand),
+                Box::new(compare::CompareCommand),
+                Box::new(links::LinksCommand),
+                Box::new(donate::DonateCommand),
+                Box::new(network::NetworkCommand),
+                Box::new(locale::LocaleCommand),
+                Box::new(config::ConfigCommand),
+                Box::new(rules::RulesCommand),
+                Box::new(subscribe::SubscribeCommand),
+                Box::new(subscribe::UnsubscribeCommand),
+                Box::new(deployments::DeploymentsCommand),
+                Box::new(watch::WatchCommand),
+                Box::new(watch::UnwatchCommand),
+                Box::new(help::HelpCommand),
+            ],
+        }
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &dyn Command> {
+        self.commands.iter().map(|command| command.as_ref())
+    }
+
+    // Look a command up by its name or one of its aliases
+    pub fn find(&self, name: &str) -> Option<&dyn Command> {
+        self.commands().find(|command| command.name() == name || command.aliases().contains(&name))
+    }
+
+    // Build the slash command definitions from each command's argument schema
+    pub fn slash_definitions(&self) -> Vec<CreateCommand> {
+        self.commands()
+            .map(|command| {
+                command.args().iter().fold(
+                    CreateCommand::new(command.name()).description(command.description()),
+                    |definition, arg| {
+                        let option_type = match arg.kind {
+                            ArgKind::Switch => CommandOptionType::Boolean,
+                            _ => CommandOptionType::String,
+                        };
+                        let option = CreateCommandOption::new(option_type, arg.name, arg.description)
+                            .required(arg.required)
+                            .set_autocomplete(matches!(arg.kind, ArgKind::Ticker | ArgKind::Network | ArgKind::Command));
+                        let option = match arg.kind {
+                            ArgKind::Choice(values) => values.iter().fold(option, |option, value| option.add_string_choice(*value, *value)),
+                            _ => option,
+                        };
+                        definition.add_option(option)
+                    },
+                )
+            })
+            .collect()
+    }
+
+    // Route a button press to the command named at the start of its custom id
+    pub async fn handle_component(&self, ctx: &Context, component: &ComponentInteraction, state: &BotState) {
+        let mut parts = component.data.custom_id.split(':');
+        let command = match parts.next().and_then(|name| self.find(name)) {
+            Some(command) => command,
+            None => return,
+        };
+
+        // A button runs its command again, so it is only open to who may use the command here
+        let settings = state.guild_settings.get(component.guild_id);
+        let roles = component.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default();
+        if let Some(denial) = access_denial(command, &settings, component.channel_id, component.user.id, &roles) {
+            let notice = CreateInteractionResponseMessage::new()
+                .content(denial)
+                .ephemeral(true);
+            if let Err(why) = component.create_response(&ctx.http, CreateInteractionResponse::Message(notice)).await {
+                println!("Failed to send the channel notice: {:?}", why);
+            }
+            return;
+        }
+
+        // Pressing a button costs the same as typing the command
+        if let Err(cooldown) = state.rate_limiter.check(command.name(), component.user.id, component.guild_id, &roles, Instant::now()) {
+            let notice = CreateInteractionResponseMessage::new()
+                .embeds(settings.theme_reply(cooldown_reply(command, cooldown)).embeds)
+                .ephemeral(true);
+            if let Err(why) = component.create_response(&ctx.http, CreateInteractionResponse::Message(notice)).await {
+                println!("Failed to send the cooldown notice: {:?}", why);
+            }
+            return;
+        }
+
+        let parts: Vec<&str> = parts.collect();
+        command.handle_component(ctx, component, state, &parts).await;
+    }
+
+    // Parse a `!command arg ...` message and run the matching command
+    pub async fn handle_message(&self, ctx: &Context, msg: &Message, state: &BotState) {
+        let settings = state.guild_settings.get(msg.guild_id);
+        let prefix = settings.prefix.as_deref().unwrap_or(PREFIX);
+
+        let mut message_parts = msg.content.split_whitespace();
+        let name = match message_parts.next().and_then(|word| word.strip_prefix(prefix)) {
+            Some(name) => name,
+            None => return, // Not a command, or a message without text
+        };
+
+        let command = match self.find(name) {
+            Some(command) => command,
+            None => return,
+        };
+
+        // Point the member to the right place in a DM instead of answering in the channel
+        let roles = msg.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default();
+        if let Some(denial) = access_denial(command, &settings, msg.channel_id, msg.author.id, &roles) {
+            if let Err(why) = msg.author.direct_message(&ctx.http, CreateMessage::new().content(denial)).await {
+                println!("Failed to send the access notice: {:?}", why);
+            }
+            return;
+        }
+
+        // Tell the user once when to come back, further attempts during the cooldown are ignored
+        if let Err(cooldown) = state.rate_limiter.check(command.name(), msg.author.id, msg.guild_id, &roles, Instant::now()) {
+            if cooldown.first {
+                settings.theme_reply(cooldown_reply(command, cooldown)).send(ctx, msg.channel_id).await;
+            }
+            return;
+        }
+
+        let invocation = Invocation {
+            ctx,
+            source: Source::Message(msg),
+            state,
+            args: CommandArgs::from_words(command.args(), message_parts),
+        };
+
+        if let Some(reply) = command.execute(&invocation).await {
+            settings.theme_reply(reply).send(ctx, msg.channel_id).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN_ARGS: &[ArgSpec] = &[
+        ArgSpec::required("ticker", "Ticker", ArgKind::Ticker),
+        ArgSpec::optional("metric", "Metric", ArgKind::Choice(&["minted", "holders"])),
+        NETWORK_FLAG,
+        EXACT_FLAG,
+    ];
+    const TEXT_ARGS: &[ArgSpec] = &[
+        ArgSpec::required("address", "Address", ArgKind::Address),
+        ArgSpec::optional("label", "Label", ArgKind::Text),
+        NETWORK_FLAG,
+    ];
+
+    fn parse(schema: &[ArgSpec], message: &str) -> CommandArgs {
+        CommandArgs::from_words(schema, message.split_whitespace())
+    }
+
+    #[test]
+    fn positional_arguments_fill_in_order() {
+        let args = parse(TOKEN_ARGS, "NACHO holders");
+        assert_eq!(args.get("ticker"), Some("NACHO"));
+        assert_eq!(args.get("metric"), Some("holders"));
+        assert_eq!(args.get("net"), None);
+        assert!(!args.switch("exact"));
+    }
+
+    #[test]
+    fn missing_optional_arguments_stay_unset() {
+        let args = parse(TOKEN_ARGS, "NACHO");
+        assert_eq!(args.get("ticker"), Some("NACHO"));
+        assert_eq!(args.get("metric"), None);
+        assert_eq!(parse(TOKEN_ARGS, "").get("ticker"), None);
+    }
+
+    #[test]
+    fn flags_take_a_value_and_switches_do_not() {
+        let args = parse(TOKEN_ARGS, "--exact NACHO --net testnet-10 minted");
+        assert_eq!(args.get("ticker"), Some("NACHO"));
+        assert_eq!(args.get("metric"), Some("minted"));
+        assert_eq!(args.get("net"), Some("testnet-10"));
+        assert!(args.switch("exact"));
+
+        // A flag at the end without its value is dropped
+        let args = parse(TOKEN_ARGS, "NACHO --net");
+        assert_eq!(args.get("ticker"), Some("NACHO"));
+        assert_eq!(args.get("net"), None);
+    }
+
+    #[test]
+    fn trailing_text_takes_the_rest() {
+        let args = parse(TEXT_ARGS, "kaspa:qq my cold wallet --net mainnet");
+        assert_eq!(args.get("address"), Some("kaspa:qq"));
+        assert_eq!(args.get("label"), Some("my cold wallet"));
+        assert_eq!(args.get("net"), Some("mainnet"));
+
+        let args = parse(TEXT_ARGS, "kaspa:qq savings");
+        assert_eq!(args.get("label"), Some("savings"));
+    }
+
+    #[test]
+    fn too_many_words_leave_the_arguments_unset() {
+        let args = parse(TOKEN_ARGS, "NACHO holders extra");
+        assert_eq!(args.get("ticker"), None);
+        assert_eq!(args.get("metric"), None);
+    }
+
+    #[test]
+    fn unknown_flags_are_read_as_words() {
+        // `--exact` is only a flag where the command declares it
+        let args = parse(TEXT_ARGS, "kaspa:qq --exact");
+        assert_eq!(args.get("label"), Some("--exact"));
+
+        let args = parse(TOKEN_ARGS, "NACHO --verbose");
+        assert_eq!(args.get("ticker"), Some("NACHO"));
+        assert_eq!(args.get("metric"), Some("--verbose"));
+        assert_eq!(parse(TOKEN_ARGS, "NACHO --verbose yes").get("ticker"), None);
+    }
+}
@@ -0,0 +1,139 @@
+// src/commands/rules.rs
+use crate::imports::*;
+use crate::commands::config::{self, parse_mentions};
+use crate::commands::registry::{mention_list, ArgKind, ArgSpec, Command, Invocation};
+use crate::guild_settings::CommandRule;
+use crate::reply::Reply;
+use crate::theme;
+use std::collections::BTreeMap;
+
+pub const NAME: &str = "rules";
+const LIMITS: &[&str] = &["channels", "roles", "users", "reset"];
+const RESET: &str = "reset"; // Clears one limit, or the whole rule when given as the limit
+
+const RULES_ARGS: &[ArgSpec] = &[
+    ArgSpec::optional("command", "Command to limit, e.g. tokenbalance", ArgKind::Command),
+    ArgSpec::optional("limit", "What to limit: channels, roles or users; reset removes the rule", ArgKind::Choice(LIMITS)),
+    ArgSpec::optional("value", "Channel, role or user mentions, or `reset`", ArgKind::Text),
+];
+
+pub struct RulesCommand;
+
+#[async_trait]
+impl Command for RulesCommand {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Shows or changes who may use a command and where (Manage Server)."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        RULES_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+        let state = invocation.state;
+
+        let guild_id = match invocation.guild_id() {
+            Some(guild_id) => guild_id,
+            None => return Some(Reply::new().embed(theme.embed()
+                .field("Not in a server", "Command rules can only be set inside a server.", false))),
+        };
+
+        if !invocation.can_manage_guild().await {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Missing permission", "You need the Manage Server permission to view or change the command rules.", false)));
+        }
+
+        let settings = state.guild_settings.get(Some(guild_id));
+        let args = &invocation.args;
+        let name = match args.get("command") {
+            Some(name) => name,
+            None => return Some(rules_reply(&settings.rules, "Command rules")),
+        };
+
+        // Rules are stored under the command's real name so aliases share them
+        let command = match state.registry.find(name) {
+            Some(command) if ![config::NAME, NAME].contains(&command.name()) => command,
+            _ => return Some(Reply::new().embed(theme.embed()
+                .field("Unknown command", format!("`{}` is not a command that can be limited.", name), false))),
+        };
+
+        if args.get("limit").is_none() {
+            let single = settings.rules.into_iter().filter(|(rule_name, _)| rule_name == command.name()).collect();
+            return Some(rules_reply(&single, "Command rule"));
+        }
+
+        // Work out the change first, it is applied to the rule as saved at that moment
+        let change: Box<dyn FnOnce(&mut CommandRule) + Send> = match (args.get("limit"), args.get("value")) {
+            (Some(RESET), _) => Box::new(|rule| *rule = CommandRule::default()),
+            (Some(limit), Some(value)) => {
+                let ids = match value.trim().eq_ignore_ascii_case(RESET) {
+                    true => Vec::new(),
+                    false => match parse_mentions(value) {
+                        Ok(ids) => ids,
+                        Err(problem) => return Some(Reply::new().embed(theme.embed()
+                            .field(format!("Invalid {}", limit), problem, false))),
+                    },
+                };
+                match limit {
+                    "channels" => Box::new(|rule| rule.channels = ids),
+                    "roles" => Box::new(|rule| rule.roles = ids),
+                    "users" => Box::new(|rule| rule.users = ids),
+                    _ => return Some(usage_reply()),
+                }
+            },
+            _ => return Some(usage_reply()),
+        };
+
+        let saved = state.guild_settings.update(guild_id, |current| {
+            let rule = current.rules.entry(command.name().to_string()).or_default();
+            change(rule);
+            if rule.is_empty() {
+                current.rules.remove(command.name());
+            }
+            current.rules.clone()
+        }).await;
+        match saved {
+            Ok(rules) => Some(rules_reply(&rules, &format!("Updated the rule for {}", command.name()))),
+            Err(e) => {
+                println!("Failed to save guild settings: {}", e);
+                Some(Reply::new().embed(theme.embed()
+                    .field("Error", "The command rules could not be saved. Please try again later.", false)))
+            },
+        }
+    }
+}
+
+// Every rule of the server, one line per command
+fn rules_reply(rules: &BTreeMap<String, CommandRule>, title: &str) -> Reply {
+    let lines: Vec<String> = rules.iter()
+        .filter(|(_, rule)| !rule.is_empty())
+        .map(|(name, rule)| {
+            let mut limits = Vec::new();
+            if !rule.channels.is_empty() {
+                limits.push(format!("in {}", mention_list(&rule.channels, "#")));
+            }
+            if !rule.roles.is_empty() {
+                limits.push(format!("roles {}", mention_list(&rule.roles, "@&")));
+            }
+            if !rule.users.is_empty() {
+                limits.push(format!("users {}", mention_list(&rule.users, "@")));
+            }
+            format!("`{}` {}", name, limits.join(" · "))
+        })
+        .collect();
+    let listing = if lines.is_empty() { "Every command can be used by everyone.".to_string() } else { lines.join("\n") };
+
+    Reply::new().embed(theme::current().embed()
+        .field(title, listing, false)
+        .field("Usage", "`!rules [command] [channels|roles|users] [mentions]` limits a command, `reset` as the value clears that limit and `!rules [command] reset` removes the rule.", false))
+}
+
+fn usage_reply() -> Reply {
+    Reply::new().embed(theme::current().embed()
+        .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!rules [command] [channels|roles|users] [mentions]`!", false))
+}
@@ -0,0 +1,119 @@
+// src/commands/slash.rs
+use crate::imports::*;
+use serenity::model::application::Command as DiscordCommand;
+use crate::commands::registry::{access_denial, cooldown_reply, ArgKind, CommandArgs, Invocation, Source, NETWORK_FLAG};
+use crate::cache::Endpoint;
+use crate::state::BotState;
+use std::time::Instant;
+
+const MAX_AUTOCOMPLETE_CHOICES: usize = 25; // Discord rejects autocomplete responses with more choices
+
+// Register the slash commands on the configured guild, or globally when no guild is set
+pub async fn register_commands(ctx: &Context, state: &BotState) {
+    let definitions = state.registry.slash_definitions();
+    let result = match state.config.command_guild_id {
+        Some(guild_id) => guild_id.set_commands(&ctx.http, definitions).await,
+        None => DiscordCommand::set_global_commands(&ctx.http, definitions).await,
+    };
+
+    match result {
+        Ok(commands) => println!("Registered {} slash commands", commands.len()),
+        Err(why) => println!("Failed to register slash commands: {:?}", why),
+    }
+}
+
+pub async fn handle_slash_command(ctx: &Context, command: &CommandInteraction, state: &BotState) {
+    let handler = match state.registry.find(&command.data.name) {
+        Some(handler) => handler,
+        None => return,
+    };
+
+    // Only the caller sees the notice, the channel stays quiet
+    let settings = state.guild_settings.get(command.guild_id);
+    let roles = command.member.as_ref().map(|member| member.roles.clone()).unwrap_or_default();
+    if let Some(denial) = access_denial(handler, &settings, command.channel_id, command.user.id, &roles) {
+        let notice = CreateInteractionResponseMessage::new()
+            .content(denial)
+            .ephemeral(true);
+        if let Err(why) = command.create_response(&ctx.http, CreateInteractionResponse::Message(notice)).await {
+            println!("Failed to send the channel notice: {:?}", why);
+        }
+        return;
+    }
+
+    if let Err(cooldown) = state.rate_limiter.check(handler.name(), command.user.id, command.guild_id, &roles, Instant::now()) {
+        let notice = CreateInteractionResponseMessage::new()
+            .embeds(settings.theme_reply(cooldown_reply(handler, cooldown)).embeds)
+            .ephemeral(true);
+        if let Err(why) = command.create_response(&ctx.http, CreateInteractionResponse::Message(notice)).await {
+            println!("Failed to send the cooldown notice: {:?}", why);
+        }
+        return;
+    }
+
+    // Fetching token data can take longer than the three seconds Discord allows for a response
+    if let Err(why) = command.defer(&ctx.http).await {
+        println!("Failed to defer interaction: {:?}", why);
+        return;
+    }
+
+    let invocation = Invocation {
+        ctx,
+        source: Source::Interaction(command),
+        state,
+        args: CommandArgs::from_interaction(command),
+    };
+
+    match handler.execute(&invocation).await {
+        Some(reply) => {
+            if let Err(why) = command.create_followup(&ctx.http, settings.theme_reply(reply).into_followup()).await {
+                println!("Error sending followup: {:?}", why);
+            }
+        },
+        None => {
+            // Clear the "thinking" indicator when there is nothing to show
+            if let Err(why) = command.delete_response(&ctx.http).await {
+                println!("Failed to delete deferred response: {:?}", why);
+            }
+        },
+    }
+}
+
+// Suggest network names, and tickers already in the cache for the selected network
+pub async fn handle_autocomplete(ctx: &Context, command: &CommandInteraction, state: &BotState) {
+    let option = match command.data.autocomplete() {
+        Some(option) => option,
+        None => return,
+    };
+
+    let kind = state.registry.find(&command.data.name)
+        .and_then(|handler| handler.args().iter().find(|arg| arg.name == option.name))
+        .map(|arg| arg.kind);
+
+    let candidates = match kind {
+        Some(ArgKind::Network) => state.networks.names().into_iter().map(str::to_string).collect(),
+        Some(ArgKind::Command) => state.registry.commands().map(|command| command.name().to_string()).collect(),
+        Some(ArgKind::Ticker) => {
+            // Use the network picked in the `net` option if it is valid, otherwise the server default
+            let args = CommandArgs::from_interaction(command);
+            let network = args.get(NETWORK_FLAG.name)
+                .and_then(|name| state.networks.get(name))
+                .or_else(|| state.guild_settings.get(command.guild_id).network.and_then(|name| state.networks.get(&name)))
+                .unwrap_or_else(|| state.networks.default_network());
+            state.cache.ids(&network.name, Endpoint::TokenInfo).await
+        },
+        _ => return,
+    };
+
+    let typed = option.value.to_uppercase();
+
+    let choices = candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_uppercase().starts_with(&typed))
+        .take(MAX_AUTOCOMPLETE_CHOICES)
+        .fold(CreateAutocompleteResponse::new(), |response, tick| response.add_string_choice(tick.clone(), tick));
+
+    if let Err(why) = command.create_response(&ctx.http, CreateInteractionResponse::Autocomplete(choices)).await {
+        println!("Failed to send autocomplete choices: {:?}", why);
+    }
+}
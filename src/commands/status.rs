@@ -1,139 +1,83 @@
 // src/commands/status.rs
 use crate::imports::*;
-use crate::mint_status::datatweaks;
-use serde_json::Value;
-use std::fs;
-use rand::distributions::{Distribution, WeightedIndex};
-use rand::thread_rng;
+use crate::mint_status::{datatweaks, velocity};
+use crate::commands::registry::{ArgKind, ArgSpec, Command, Invocation, EXACT_FLAG, NETWORK_FLAG};
+use crate::charts::{Metric, Range};
+use crate::commands::api_error::api_error_reply;
+use crate::commands::chart;
+use crate::format::NumberFormat;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::state::BotState;
+use crate::theme;
 
-// Helper function to select a random background image based on weight
-fn select_random_banner(banners: &Vec<Value>) -> &str {
-    let weights: Vec<_> = banners.iter().map(|b| b["weight"].as_u64().unwrap_or(1) as u32).collect();
-    let dist = WeightedIndex::new(&weights).unwrap();
-    let mut rng = thread_rng();
-    let index = dist.sample(&mut rng);
-    banners[index]["url"].as_str().unwrap()
-}
 
-pub async fn handle_status_command(ctx: &Context, msg: &Message, message_parts: &mut std::str::SplitWhitespace<'_>, api_base_url: &str) {
-    let message_word_count = msg.content.split_whitespace().count();
+const TICKER_ARGS: &[ArgSpec] = &[ArgSpec::optional("ticker", "KRC20 ticker, e.g. NACHO (default: the server's ticker)", ArgKind::Ticker), NETWORK_FLAG, EXACT_FLAG];
+
+pub struct StatusCommand;
 
-    // Load the JSON template
-    let template_content = match fs::read_to_string("message_template.json") {
-        Ok(content) => content,
-        Err(e) => {
-            println!("Failed to read message template: {:?}", e);
-            return;
-        }
-    };
+#[async_trait]
+impl Command for StatusCommand {
+    fn name(&self) -> &'static str {
+        "tokeninfo"
+    }
 
-    let template: Value = match serde_json::from_str(&template_content) {
-        Ok(template) => template,
-        Err(e) => {
-            println!("Failed to parse message template: {:?}", e);
-            return;
-        }
-    };
+    fn aliases(&self) -> &'static [&'static str] {
+        &["mint_status"]
+    }
 
-    // Ensure required fields are present in the template
-    let color = match template["color"].as_u64() {
-        Some(color) => color as u32,
-        None => {
-            println!("Color not found in message template");
-            return;
-        }
-    };
+    fn description(&self) -> &'static str {
+        "Displays the mint status for the given token."
+    }
 
-    let background_images = match template["background_images"].as_array() {
-        Some(images) => images,
-        None => {
-            println!("Background images not found in message template");
-            return;
-        }
-    };
+    fn args(&self) -> &'static [ArgSpec] {
+        TICKER_ARGS
+    }
 
-    let background_image_url = select_random_banner(background_images);
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+        status_reply(invocation.state, network, &invocation.number_format(), invocation.ticker().as_deref()).await
+    }
+}
 
-    let author_name = match template["author"]["name"].as_str() {
-        Some(name) => name,
-        None => {
-            println!("Author name not found in message template");
-            return;
-        }
-    };
+// Build the mint status reply for a ticker, shared by the prefix and slash commands
+pub async fn status_reply(state: &BotState, network: &Network, format: &NumberFormat, ticker: Option<&str>) -> Option<Reply> {
+    let theme = theme::current();
 
-    let author_icon_url = match template["author"]["icon_url"].as_str() {
-        Some(url) => url,
-        None => {
-            println!("Author icon URL not found in message template");
-            return;
-        }
-    };
+    if let Some(ticker) = ticker {
+        let token = ticker.to_uppercase();
 
-    if message_word_count == 2 {
-        let token = message_parts.next().unwrap_or("").to_uppercase();
-        let should_fetch;
+        // The cache only calls the API once the data is older than its time to live
+        let data = match datatweaks::view_token(state.cache.as_ref(), &state.storage, network, &token).await {
+            Ok(data) => data,
+            Err(e) => {
+                println!("Failed to fetch token data: {}", e);
+                return Some(api_error_reply(&e, "!tokeninfo [ticker]", network));
+            }
+        };
 
-        let fetch_result = if let Ok((fetch, _)) = datatweaks::check_time(&token) {
-            should_fetch = fetch;
-            if should_fetch {
-                match datatweaks::fetch_from_api(api_base_url, &token).await {
-                    Ok(data) => {
-                        datatweaks::save_data(&data, &token).expect("Failed to save data");
-                        data
-                    },
-                    Err(e) => {
-                        if e.to_string().contains("invalid type: null, expected a sequence") {
-                            // Use the template to create the error message
-                            let token_error = CreateMessage::new().embed(CreateEmbed::new()
-                                .color(color)
-                                .image(background_image_url)
-                                .field("Invalid token", "Make sure to provide a valid token for: `!tokeninfo [ticker]`!", false)
-                                .author(
-                                    CreateEmbedAuthor::new(author_name)
-                                        .icon_url(author_icon_url)
-                                ));
-                            if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), token_error).await {
-                                println!("Error sending message: {:?}", why);
-                            }
-                        } else {
-                            println!("Failed to fetch from API: {}", e);
-                        }
-                        return;
-                    }
-                }
-            } else {
-                match datatweaks::fetch_from_json(&token) {
-                    Ok(data) => data,
-                    Err(e) => {
-                        println!("Failed to fetch from JSON: {}", e);
-                        return;
-                    }
-                }
+        // Velocity over the longest window, measured from the recorded history
+        let history_start = data.fetched_at.saturating_sub(velocity::WINDOWS[2].1);
+        let velocity = match state.storage.token_history(&network.name, &token, history_start).await {
+            Ok(history) => Some(velocity::mint_velocity(&data.value.result[0], &history, data.fetched_at)),
+            Err(e) => {
+                println!("Failed to read the mint history of {}: {}", token, e);
+                None
             }
-        } else {
-            println!("Failed to check time");
-            return;
         };
 
-        let formatted_message = datatweaks::format_data(fetch_result).await;
-        if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), formatted_message).await {
-            println!("Error sending message: {:?}", why);
-        } else {
-            println!("Token: {}, Should Fetch: {}", token, should_fetch);
-        }
+        // Mint progress over the last week, left out until there is some history to draw
+        let chart = chart::chart_attachment(state, network, &data, Metric::Mint, Range::Week).await.ok();
+
+        println!("Token: {}, Network: {}, Stale: {}", token, network.name, data.stale);
+        let distribution = state.config.distribution_section.then_some(&state.config.concentration);
+        Some(datatweaks::format_data(network, format, data, velocity, distribution, chart))
     } else {
         // Use the template to create the parameter error message
-        let paramater_error = CreateMessage::new().embed(CreateEmbed::new()
-            .color(color)
-            .image(background_image_url)
-            .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!tokeninfo [ticker]`!", false)
-            .author(
-                CreateEmbedAuthor::new(author_name)
-                    .icon_url(author_icon_url)
-            ));
-        if let Err(why) = msg.channel_id.send_message(ctx.http.clone(), paramater_error).await {
-            println!("Error sending message: {:?}", why);
-        }
+        Some(Reply::new().embed(theme.embed()
+            .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!tokeninfo [ticker]`!", false)))
     }
 }
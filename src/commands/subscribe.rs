@@ -0,0 +1,196 @@
+// src/commands/subscribe.rs
+use crate::imports::*;
+use crate::cache::current_time;
+use crate::commands::api_error::api_error_reply;
+use crate::commands::registry::{ArgKind, ArgSpec, Command, Invocation, NETWORK_FLAG};
+use crate::mint_status::{datatweaks, milestones};
+use crate::reply::Reply;
+use crate::storage::{Subscription, SubscriptionKind};
+use crate::theme;
+
+const SUBSCRIBE_ARGS: &[ArgSpec] = &[
+    ArgSpec::optional("ticker", "KRC20 ticker, e.g. NACHO", ArgKind::Ticker),
+    ArgSpec::optional("alerts", "What to post in this channel", ArgKind::Choice(SubscriptionKind::NAMES)),
+    NETWORK_FLAG,
+];
+
+pub struct SubscribeCommand;
+
+#[async_trait]
+impl Command for SubscribeCommand {
+    fn name(&self) -> &'static str {
+        "subscribe"
+    }
+
+    fn description(&self) -> &'static str {
+        "Posts alerts about a token in this channel, e.g. mint milestones (Manage Server)."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        SUBSCRIBE_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+        let state = invocation.state;
+
+        let guild_id = match invocation.guild_id() {
+            Some(guild_id) => guild_id,
+            None => return Some(Reply::new().embed(theme.embed()
+                .field("Not in a server", "Alerts can only be posted in a server channel.", false))),
+        };
+
+        let (ticker, kind) = match (invocation.args.get("ticker"), invocation.args.get("alerts")) {
+            (Some(ticker), Some(kind)) => (ticker.to_uppercase(), kind),
+            (None, None) => return Some(list_reply(invocation).await),
+            _ => return Some(usage_reply("!subscribe [ticker] [milestones|whales]")),
+        };
+        let kind = match SubscriptionKind::from_name(kind) {
+            Some(kind) => kind,
+            None => return Some(usage_reply("!subscribe [ticker] [milestones|whales]")),
+        };
+
+        if !invocation.can_manage_guild().await {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Missing permission", "You need the Manage Server permission to change the alerts of a channel.", false)));
+        }
+
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+
+        // Look the token up through the status data path, which also tells whether it exists
+        let data = match datatweaks::fetch_token(state.cache.as_ref(), &state.storage, network, &ticker).await {
+            Ok(data) => data,
+            Err(e) => {
+                println!("Failed to fetch token data: {}", e);
+                return Some(api_error_reply(&e, "!subscribe [ticker] [milestones|whales]", network));
+            },
+        };
+        let thresholds = &state.config.milestone_thresholds;
+        let reached = match milestones::reached_milestone(&data.value.result[0], thresholds) {
+            Some(reached) => reached,
+            None => return Some(Reply::new().embed(theme.embed()
+                .field("Not deployed", format!("{} has not been deployed on {}.", ticker, network.label), false))),
+        };
+
+        let mut subscription = Subscription {
+            channel_id: invocation.channel_id().get(),
+            guild_id: guild_id.get(),
+            network: network.name.clone(),
+            tick: ticker.clone(),
+            kind,
+            last_milestone: 0,
+            last_snapshot: 0,
+        };
+        let confirmation = match kind {
+            SubscriptionKind::Milestones => {
+                // Milestones already passed are not announced, only the ones still ahead
+                let ahead: Vec<String> = thresholds.iter().filter(|percent| **percent > reached).map(|percent| format!("{}%", percent)).collect();
+                if ahead.is_empty() {
+                    return Some(Reply::new().embed(theme.embed()
+                        .field("Nothing left to announce", format!("{} has passed every milestone on {}.", ticker, network.label), false)));
+                }
+                subscription.last_milestone = reached;
+                format!("This channel will be told when {} on {} passes {} minted.", ticker, network.label, ahead.join(", "))
+            },
+            SubscriptionKind::Whales => {
+                // Moves are measured from the holder list as it is now
+                match state.storage.latest_holder_snapshot(&network.name, &ticker).await {
+                    Ok(Some(snapshot)) => subscription.last_snapshot = snapshot.id,
+                    Ok(None) => {},
+                    Err(e) => println!("Failed to read the latest snapshot of {}: {}", ticker, e),
+                }
+                let whales = &state.config.whale_thresholds;
+                format!(
+                    "This channel will be told when the top holder of {} on {} changes, an address enters the top 10 or one of the top {} holders' balance changes by more than {}%.",
+                    ticker, network.label, whales.top_n, whales.change_pct,
+                )
+            },
+        };
+
+        if let Err(e) = state.storage.subscribe(subscription, current_time()).await {
+            println!("Failed to save the subscription: {}", e);
+            return Some(Reply::new().embed(theme.embed()
+                .field("Error", "The subscription could not be saved. Please try again later.", false)));
+        }
+
+        Some(Reply::new().embed(theme.embed().field("Subscribed", confirmation, false)))
+    }
+}
+
+pub struct UnsubscribeCommand;
+
+#[async_trait]
+impl Command for UnsubscribeCommand {
+    fn name(&self) -> &'static str {
+        "unsubscribe"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stops the alerts about a token in this channel (Manage Server)."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        SUBSCRIBE_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+
+        let (ticker, kind) = match (invocation.args.get("ticker"), invocation.args.get("alerts").and_then(SubscriptionKind::from_name)) {
+            (Some(ticker), Some(kind)) => (ticker.to_uppercase(), kind),
+            _ => return Some(usage_reply("!unsubscribe [ticker] [milestones|whales]")),
+        };
+
+        if invocation.guild_id().is_none() || !invocation.can_manage_guild().await {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Missing permission", "You need the Manage Server permission to change the alerts of a channel.", false)));
+        }
+
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+
+        let removed = invocation.state.storage.unsubscribe(invocation.channel_id().get(), &network.name, &ticker, kind).await;
+        let message = match removed {
+            Ok(true) => format!("This channel will no longer get {} {} alerts on {}.", ticker, kind.name(), network.label),
+            Ok(false) => format!("This channel was not subscribed to {} {} alerts on {}.", ticker, kind.name(), network.label),
+            Err(e) => {
+                println!("Failed to remove the subscription: {}", e);
+                "The subscription could not be removed. Please try again later.".to_string()
+            },
+        };
+        Some(Reply::new().embed(theme.embed().field("Unsubscribe", message, false)))
+    }
+}
+
+// The alerts this channel gets
+async fn list_reply(invocation: &Invocation<'_>) -> Reply {
+    let state = invocation.state;
+    let lines = match state.storage.subscriptions(Some(invocation.channel_id().get()), None).await {
+        Ok(subscriptions) if subscriptions.is_empty() => "This channel has no alerts.".to_string(),
+        Ok(subscriptions) => subscriptions.iter()
+            .map(|subscription| {
+                let network = state.networks.get(&subscription.network).map(|network| network.label.as_str()).unwrap_or(&subscription.network);
+                format!("`{}` {} on {}", subscription.tick, subscription.kind.name(), network)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(e) => {
+            println!("Failed to list the subscriptions: {}", e);
+            "The subscriptions could not be loaded. Please try again later.".to_string()
+        },
+    };
+
+    Reply::new().embed(theme::current().embed()
+        .field("Alerts in this channel", lines, false)
+        .field("Usage", "`!subscribe [ticker] milestones` posts mint milestones here, `!subscribe [ticker] whales` posts large moves among the top holders and `!unsubscribe [ticker] [milestones|whales]` stops them.", false))
+}
+
+fn usage_reply(usage: &str) -> Reply {
+    Reply::new().embed(theme::current().embed()
+        .field("Wrong Number of Parameters", format!("Make sure to use the correct format for: `{}`!", usage), false))
+}
@@ -0,0 +1,153 @@
+// src/commands/topholders.rs
+use crate::imports::*;
+use crate::amount::TokenAmounts;
+use crate::cache::{current_time, Cached};
+use crate::commands::api_error::api_error_reply;
+use crate::commands::registry::{self, ArgKind, ArgSpec, Command, Invocation, EXACT_FLAG, NETWORK_FLAG};
+use crate::format::{Field, NumberFormat};
+use crate::mint_status::datatweaks;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::state::BotState;
+use crate::theme;
+use crate::DataStruct;
+
+const TICKER_ARGS: &[ArgSpec] = &[ArgSpec::optional("ticker", "KRC20 ticker, e.g. NACHO (default: the server's ticker)", ArgKind::Ticker), NETWORK_FLAG, EXACT_FLAG];
+const NAME: &str = "topholders";
+const PAGE_SIZE: usize = 10; // Holders per page
+
+pub struct TopHoldersCommand;
+
+#[async_trait]
+impl Command for TopHoldersCommand {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Displays the largest holders of the given token."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        TICKER_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+
+        let ticker = match invocation.ticker() {
+            Some(ticker) => ticker.to_uppercase(),
+            None => return Some(Reply::new().embed(theme::current().embed()
+                .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!topholders [ticker]`!", false))),
+        };
+
+        let state = invocation.state;
+        let expires_at = current_time() + state.config.button_timeout.as_secs();
+        match datatweaks::view_token(state.cache.as_ref(), &state.storage, network, &ticker).await {
+            Ok(data) => Some(page_reply(network, &invocation.number_format(), &data, 0, expires_at)),
+            Err(e) => {
+                println!("Failed to fetch token data: {}", e);
+                Some(api_error_reply(&e, "!topholders [ticker]", network))
+            },
+        }
+    }
+
+    // Previous/next buttons carry everything needed to redraw the page: `topholders:network:ticker:page:expires_at:exact`
+    async fn handle_component(&self, ctx: &Context, component: &ComponentInteraction, state: &BotState, parts: &[&str]) {
+        let (network, ticker, page, expires_at, exact) = match parts {
+            [network, ticker, page, expires_at, exact] => match (state.networks.get(network), page.parse::<usize>(), expires_at.parse::<u64>()) {
+                (Some(network), Ok(page), Ok(expires_at)) => (network, *ticker, page, expires_at, *exact == "true"),
+                _ => return,
+            },
+            _ => return,
+        };
+        let format = registry::number_format(state, component.guild_id, exact);
+        let settings = state.guild_settings.get(component.guild_id);
+
+        // Acknowledge right away, the page may need a fresh API call
+        if let Err(why) = component.defer(&ctx.http).await {
+            println!("Failed to defer button press: {:?}", why);
+            return;
+        }
+
+        let reply = if current_time() > expires_at {
+            // Keep the page as it is but take the buttons away
+            EditInteractionResponse::new().components(Vec::new())
+        } else {
+            match datatweaks::view_token(state.cache.as_ref(), &state.storage, network, ticker).await {
+                Ok(data) => settings.theme_reply(page_reply(network, &format, &data, page, expires_at)).into_edit(),
+                Err(e) => {
+                    println!("Failed to fetch token data: {}", e);
+                    settings.theme_reply(api_error_reply(&e, "!topholders [ticker]", network)).into_edit()
+                },
+            }
+        };
+
+        if let Err(why) = component.edit_response(&ctx.http, reply).await {
+            println!("Failed to update the holder list: {:?}", why);
+        }
+    }
+}
+
+// One page of the holder leaderboard with the buttons to move between pages
+fn page_reply(network: &Network, format: &NumberFormat, data: &Cached<DataStruct>, page: usize, expires_at: u64) -> Reply {
+    let theme = theme::current();
+    let result = &data.value.result[0];
+    let tick = result.tick.to_uppercase();
+    let holders = result.holder.as_deref().unwrap_or_default();
+
+    let pages = holders.len().div_ceil(PAGE_SIZE).max(1);
+    let page = page.min(pages - 1);
+
+    let amounts = TokenAmounts::new(&result.tick, &result.dec);
+    let max = amounts.parse(&result.max).ok();
+
+    let lines: Vec<String> = holders.iter()
+        .enumerate()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .map(|(index, holder)| {
+            let amount = amounts.parse(&holder.amount);
+            let share = match (&amount, &max) {
+                (Ok(amount), Some(max)) => amount.percent_of(max).map(|share| format.percent(share)),
+                _ => None,
+            };
+            format!(
+                "`#{:>2}` {} · {} ({})",
+                index + 1,
+                network.address_link(&holder.address),
+                format.amount_or_na(&amount, Field::Holding),
+                share.as_deref().unwrap_or("N/A"),
+            )
+        })
+        .collect();
+
+    let mut description = format!("Network: **{}**", network.label);
+    if data.stale {
+        description.push_str("\n⚠️ The Kasplex API is unavailable, data may be stale.");
+    }
+    let table = if lines.is_empty() { "No holders yet.".to_string() } else { lines.join("\n") };
+
+    let embed = theme.status_embed()
+        .description(format!("{}\n\n{}", description, table))
+        .field("Page", format!("{} of {} · % of max supply", page + 1, pages), false);
+
+    let button = |target: usize, label: &str, disabled: bool| {
+        CreateButton::new(format!("{}:{}:{}:{}:{}:{}", NAME, network.name, tick, target, expires_at, format.exact))
+            .label(label)
+            .style(ButtonStyle::Secondary)
+            .disabled(disabled)
+    };
+    let buttons = CreateActionRow::Buttons(vec![
+        button(page.saturating_sub(1), "◀ Previous", page == 0),
+        button(page + 1, "Next ▶", page + 1 >= pages),
+    ]);
+
+    Reply::new()
+        .content(format!("**# Top Holders of {}**", tick))
+        .embed(embed)
+        .components(vec![buttons])
+}
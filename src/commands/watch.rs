@@ -0,0 +1,186 @@
+// src/commands/watch.rs
+use crate::imports::*;
+use crate::address::Address;
+use crate::cache::current_time;
+use crate::commands::registry::{ArgKind, ArgSpec, Command, Invocation, NETWORK_FLAG};
+use crate::holder_status::datatweaks;
+use crate::reply::Reply;
+use crate::state::BotState;
+use crate::storage::WatchedAddress;
+use crate::theme;
+
+const MAX_LABEL_LEN: usize = 32;
+
+const WATCH_ARGS: &[ArgSpec] = &[
+    ArgSpec::optional("wallet-address", "Kaspa wallet address to watch", ArgKind::Address),
+    ArgSpec::optional("label", "Name shown in the notifications, e.g. cold wallet", ArgKind::Text),
+    NETWORK_FLAG,
+];
+
+const UNWATCH_ARGS: &[ArgSpec] = &[ArgSpec::required("wallet-address", "Kaspa wallet address to stop watching", ArgKind::Address), NETWORK_FLAG];
+
+pub struct WatchCommand;
+
+#[async_trait]
+impl Command for WatchCommand {
+    fn name(&self) -> &'static str {
+        "watch"
+    }
+
+    fn description(&self) -> &'static str {
+        "Sends you a DM when the KRC20 balances of a wallet address change."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        WATCH_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+        let state = invocation.state;
+        let user_id = invocation.user_id().get();
+
+        let input = match invocation.args.get("wallet-address") {
+            Some(input) => input,
+            None => return Some(private_reply(invocation, watchlist_reply(state, user_id, "Your watched addresses").await).await),
+        };
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+        let address = match Address::parse(input, &network.address_prefix) {
+            Ok(address) => address.to_string(),
+            Err(e) => return Some(private_reply(invocation, Reply::new().embed(theme.embed()
+                .field("Invalid address", format!("`{}` cannot be used on {}: {}.", input.trim(), network.label, e), false))).await),
+        };
+        let label = invocation.args.get("label").map(str::trim).filter(|label| !label.is_empty());
+        if label.is_some_and(|label| label.chars().count() > MAX_LABEL_LEN) {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Invalid label", format!("The label can be at most {} characters.", MAX_LABEL_LEN), false)));
+        }
+
+        let watches = match state.storage.watched_addresses(Some(user_id)).await {
+            Ok(watches) => watches,
+            Err(e) => return Some(storage_error_reply(e)),
+        };
+        let known = watches.iter().any(|watch| watch.network == network.name && watch.address == address);
+        if !known && watches.len() >= state.config.watch_limit {
+            return Some(Reply::new().embed(theme.embed()
+                .field("Watchlist full", format!("You can watch up to {} addresses. Remove one with `!unwatch [wallet-address]` first.", state.config.watch_limit), false)));
+        }
+
+        // Start from the current balances so the first DM is about a real change
+        let balances = match datatweaks::fetch_holder_data(state.cache.as_ref(), network, &address).await {
+            Ok(data) if !data.stale => Some(data.value.result),
+            _ => None,
+        };
+        let watch = WatchedAddress {
+            user_id,
+            network: network.name.clone(),
+            address: address.clone(),
+            label: label.map(str::to_string),
+            balances,
+            failures: 0,
+            next_check: current_time() + state.config.watch_interval.as_secs(),
+        };
+        if let Err(e) = state.storage.watch(watch, current_time()).await {
+            return Some(storage_error_reply(e));
+        }
+
+        Some(private_reply(invocation, watchlist_reply(state, user_id, &format!("Watching {}", network.address_link(&address))).await).await)
+    }
+}
+
+pub struct UnwatchCommand;
+
+#[async_trait]
+impl Command for UnwatchCommand {
+    fn name(&self) -> &'static str {
+        "unwatch"
+    }
+
+    fn description(&self) -> &'static str {
+        "Stops the DMs about a watched wallet address."
+    }
+
+    fn args(&self) -> &'static [ArgSpec] {
+        UNWATCH_ARGS
+    }
+
+    async fn execute(&self, invocation: &Invocation<'_>) -> Option<Reply> {
+        let theme = theme::current();
+        let state = invocation.state;
+        let user_id = invocation.user_id().get();
+
+        let input = match invocation.args.get("wallet-address") {
+            Some(input) => input,
+            None => return Some(Reply::new().embed(theme.embed()
+                .field("Wrong Number of Parameters", "Make sure to use the correct format for: `!unwatch [wallet-address]`!", false))),
+        };
+        let network = match invocation.network() {
+            Ok(network) => network,
+            Err(reply) => return Some(reply),
+        };
+        // Fall back to the text as typed so an address watched before a network was removed can still go
+        let address = Address::parse(input, &network.address_prefix)
+            .map(|address| address.to_string())
+            .unwrap_or_else(|_| input.trim().to_lowercase());
+
+        match state.storage.unwatch(user_id, &address).await {
+            Ok(true) => Some(private_reply(invocation, watchlist_reply(state, user_id, "Stopped watching the address").await).await),
+            Ok(false) => Some(private_reply(invocation, Reply::new().embed(theme.embed()
+                .field("Not watched", format!("`{}` is not on your watchlist.", address), false))).await),
+            Err(e) => Some(storage_error_reply(e)),
+        }
+    }
+}
+
+// The watchlist links a Discord account to its wallets, so in a server anything naming a watched address goes by DM
+// and the channel only gets a pointer
+async fn private_reply(invocation: &Invocation<'_>, reply: Reply) -> Reply {
+    if invocation.guild_id().is_none() {
+        return reply;
+    }
+    let notice = match invocation.user_id().direct_message(invocation.ctx, reply.into_message()).await {
+        Ok(_) => "Check your DMs, watchlist replies are sent there.",
+        Err(why) => {
+            println!("Failed to DM the watchlist to {}: {:?}", invocation.user_id(), why);
+            "Your watchlist is only sent by DM, and the DM could not be delivered. Allow direct messages from server members and try again."
+        },
+    };
+    Reply::new().embed(theme::current().embed().field("Watchlist", notice, false))
+}
+
+// The user's watched addresses with their labels
+async fn watchlist_reply(state: &BotState, user_id: u64, title: &str) -> Reply {
+    let lines = match state.storage.watched_addresses(Some(user_id)).await {
+        Ok(watches) if watches.is_empty() => "You are not watching any address.".to_string(),
+        Ok(watches) => watches.iter()
+            .map(|watch| {
+                let link = match state.networks.get(&watch.network) {
+                    Some(network) => format!("{} on {}", network.address_link(&watch.address), network.label),
+                    None => format!("`{}` on {}", watch.address, watch.network),
+                };
+                match &watch.label {
+                    Some(label) => format!("**{}** {}", label, link),
+                    None => link,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(e) => {
+            println!("Failed to list the watched addresses: {}", e);
+            "Your watchlist could not be loaded. Please try again later.".to_string()
+        },
+    };
+
+    Reply::new().embed(theme::current().embed()
+        .field(title, lines, false)
+        .field("Usage", format!("`!watch [wallet-address] [label]` sends you a DM when its balances change (up to {} addresses), `!unwatch [wallet-address]` stops it.", state.config.watch_limit), false))
+}
+
+fn storage_error_reply(e: impl std::fmt::Display) -> Reply {
+    println!("Failed to update the watchlist: {}", e);
+    Reply::new().embed(theme::current().embed()
+        .field("Error", "Your watchlist could not be saved. Please try again later.", false))
+}
@@ -0,0 +1,170 @@
+// src/config.rs
+use crate::imports::*;
+use crate::cache::CacheTtls;
+use crate::format::Locale;
+use crate::holder_status::analytics::ConcentrationThresholds;
+use crate::holder_status::whales::WhaleThresholds;
+use crate::mint_status::milestones::{self, QuietHours};
+use crate::rate_limit::{BucketLimit, RateLimits};
+use crate::retry::{CircuitBreaker, RetryPolicy};
+use serenity::model::id::GuildId;
+use std::time::Duration;
+
+// Runtime settings read from the environment when the bot starts
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub api_base_url: Option<String>, // Overrides the URL of the default network from networks.json
+    pub prefix_commands: bool, // Keep answering the old `!command` messages while users move to slash commands
+    pub command_guild_id: Option<GuildId>, // Register slash commands on a single guild instead of globally
+    pub reload_interval: Duration, // How often the JSON configuration files are checked for changes
+    pub retry: RetryPolicy, // Retries for failed Kasplex requests
+    pub breaker_threshold: u32, // Failed Kasplex requests in a row before the circuit breaker opens
+    pub breaker_cooldown: Duration, // How long the breaker stays open before trying the API again
+    pub cache_ttls: CacheTtls, // How long each kind of API response is served from the cache
+    pub cache_max_age: Duration, // Expired entries are kept this long as a fallback while the API is down
+    pub cache_snapshot: bool, // Save the cache to the database so a restart starts warm
+    pub cache_snapshot_interval: Duration, // How often the cache is pruned and the snapshot written
+    pub database_path: String, // SQLite file with the token history
+    pub history_interval: Duration, // How often tokens that are still minting are sampled for the history
+    pub history_max_age: Duration, // Snapshots older than this are deleted, older than a day they are thinned out to one per hour
+    pub distribution_section: bool, // Show holder concentration measures in !tokeninfo
+    pub concentration: ConcentrationThresholds, // Concentration levels marked with a warning
+    pub button_timeout: Duration, // Paging buttons stop working this long after the message was sent
+    pub default_locale: Locale, // Number separators for guilds that did not pick a locale
+    pub rate_limits: RateLimits, // How often a user and a guild may use each command
+    pub milestone_interval: Duration, // How often subscribed tokens are checked for new mint milestones
+    pub milestone_thresholds: Vec<u32>, // Mint percentages announced to subscribed channels, ascending
+    pub quiet_hours: Option<QuietHours>, // Hours (UTC) in which alerts wait, for guilds that did not set their own
+    pub deployment_interval: Duration, // How often the token lists are checked for new deployments
+    pub watch_interval: Duration, // How often each watched address is checked for balance changes
+    pub watch_max_backoff: Duration, // Longest wait between checks of an address whose checks keep failing
+    pub watch_limit: usize, // Addresses one user may watch
+    pub whale_interval: Duration, // How often the holder lists of tokens with whale subscriptions are compared
+    pub whale_thresholds: WhaleThresholds, // Which holder moves are posted
+    pub compare_max_tickers: usize, // Tokens one !compare may list
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        // Read the API base URL override from the environment variable, networks.json has the defaults
+        let api_base_url = env::var("KASPLEX_API_BASE_URL").ok();
+
+        let prefix_commands = env::var("ENABLE_PREFIX_COMMANDS")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(true);
+
+        // Guild registration updates instantly, global registration can take a while to show up
+        let command_guild_id = env::var("DISCORD_GUILD_ID")
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(GuildId::new);
+
+        let reload_interval = Duration::from_secs(env_u64("CONFIG_RELOAD_INTERVAL_SECS", 10));
+
+        let retry = RetryPolicy {
+            max_retries: env_u64("KASPLEX_MAX_RETRIES", 3) as u32,
+            base_delay: Duration::from_millis(env_u64("KASPLEX_RETRY_BASE_MS", 500)),
+            max_delay: Duration::from_millis(env_u64("KASPLEX_RETRY_MAX_MS", 8000)),
+        };
+
+        let cache_ttls = CacheTtls {
+            token_info: Duration::from_secs(env_u64("CACHE_TTL_TOKEN_INFO_SECS", 600)),
+            holder_list: Duration::from_secs(env_u64("CACHE_TTL_HOLDER_LIST_SECS", 900)),
+            address_balances: Duration::from_secs(env_u64("CACHE_TTL_ADDRESS_BALANCES_SECS", 60)),
+        };
+
+        let cache_snapshot = env::var("CACHE_SNAPSHOT")
+            .map(|value| parse_bool(&value))
+            .unwrap_or(true);
+
+        let rate_limits = RateLimits {
+            user: BucketLimit {
+                burst: env_u64("RATE_LIMIT_USER_BURST", 3) as u32,
+                per_minute: env_f64("RATE_LIMIT_USER_PER_MIN", 6.0),
+            },
+            guild: BucketLimit {
+                burst: env_u64("RATE_LIMIT_GUILD_BURST", 20) as u32,
+                per_minute: env_f64("RATE_LIMIT_GUILD_PER_MIN", 60.0),
+            },
+            // Comma separated role ids, e.g. the moderators
+            exempt_roles: env::var("RATE_LIMIT_EXEMPT_ROLES")
+                .map(|value| value.split(',').filter_map(|id| id.trim().parse().ok()).collect())
+                .unwrap_or_default(),
+        };
+
+        // An unset or unusable list keeps the default milestones
+        let milestone_thresholds = env::var("MILESTONE_THRESHOLDS")
+            .map(|value| milestones::parse_thresholds(&value))
+            .ok()
+            .filter(|thresholds| !thresholds.is_empty())
+            .unwrap_or_else(|| milestones::DEFAULT_THRESHOLDS.to_vec());
+
+        let database_path = env::var("DATABASE_PATH").unwrap_or_else(|_| "data_storage/nacho_bot.sqlite3".to_string());
+
+        Config {
+            api_base_url,
+            prefix_commands,
+            command_guild_id,
+            reload_interval,
+            retry,
+            breaker_threshold: env_u64("KASPLEX_BREAKER_THRESHOLD", 5) as u32,
+            breaker_cooldown: Duration::from_secs(env_u64("KASPLEX_BREAKER_COOLDOWN_SECS", 60)),
+            cache_ttls,
+            cache_max_age: Duration::from_secs(env_u64("CACHE_MAX_AGE_SECS", 86400)),
+            cache_snapshot,
+            cache_snapshot_interval: Duration::from_secs(env_u64("CACHE_SNAPSHOT_INTERVAL_SECS", 300)),
+            database_path,
+            history_interval: Duration::from_secs(env_u64("HISTORY_INTERVAL_SECS", 600).max(60)),
+            history_max_age: Duration::from_secs(env_u64("HISTORY_MAX_AGE_DAYS", 90).max(7) * 24 * 3600),
+            distribution_section: env::var("SHOW_DISTRIBUTION").map(|value| parse_bool(&value)).unwrap_or(true),
+            concentration: ConcentrationThresholds {
+                top10_share: env_f64("WARN_TOP10_SHARE_PCT", 50.0),
+                gini: env_f64("WARN_GINI", 0.8),
+                nakamoto: env_u64("WARN_NAKAMOTO", 3) as usize,
+                hhi: env_f64("WARN_HHI", 2500.0),
+            },
+            button_timeout: Duration::from_secs(env_u64("BUTTON_TIMEOUT_SECS", 600)),
+            default_locale: env::var("DEFAULT_LOCALE").ok().and_then(|name| Locale::parse(&name)).unwrap_or_default(),
+            rate_limits,
+            milestone_interval: Duration::from_secs(env_u64("MILESTONE_INTERVAL_SECS", 300).max(60)),
+            milestone_thresholds,
+            quiet_hours: env::var("QUIET_HOURS").ok().and_then(|value| QuietHours::parse(&value)),
+            deployment_interval: Duration::from_secs(env_u64("DEPLOYMENT_INTERVAL_SECS", 300).max(60)),
+            watch_interval: Duration::from_secs(env_u64("WATCH_INTERVAL_SECS", 300).max(60)),
+            watch_max_backoff: Duration::from_secs(env_u64("WATCH_MAX_BACKOFF_SECS", 3600)),
+            watch_limit: env_u64("WATCH_LIMIT", 5) as usize,
+            whale_interval: Duration::from_secs(env_u64("WHALE_INTERVAL_SECS", 900).max(60)),
+            whale_thresholds: WhaleThresholds {
+                top_n: env_u64("WHALE_TOP_N", 10) as usize,
+                change_pct: env_f64("WHALE_CHANGE_PCT", 10.0),
+            },
+            compare_max_tickers: (env_u64("COMPARE_MAX_TICKERS", 5) as usize).clamp(2, 10),
+        }
+    }
+
+    // A fresh circuit breaker with the configured limits
+    pub fn circuit_breaker(&self) -> CircuitBreaker {
+        CircuitBreaker::new(self.breaker_threshold, self.breaker_cooldown)
+    }
+}
+
+// Accept the usual spellings for boolean environment variables
+fn parse_bool(value: &str) -> bool {
+    matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+// Read a numeric environment variable, falling back to the default when unset or invalid
+fn env_u64(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+fn env_f64(name: &str, default: f64) -> f64 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|value: &f64| value.is_finite())
+        .unwrap_or(default)
+}
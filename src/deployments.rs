@@ -0,0 +1,230 @@
+// src/deployments.rs
+use crate::imports::*;
+use crate::amount::{TokenAmount, TokenAmounts};
+use crate::cache::current_time;
+use crate::commands::registry::number_format;
+use crate::format::{Field, NumberFormat};
+use crate::kasplex::{Cursor, KasplexError};
+use crate::mint_status::datatweaks::format_pre_allocation;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::result_struct::ResultStruct;
+use crate::state::BotState;
+use crate::storage::DeploymentFeed;
+use crate::theme;
+use serenity::http::Http;
+use serenity::model::id::GuildId;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+const MAX_PAGES: usize = 20; // Pages read per run; after a long outage older deployments are skipped
+
+// Check the token list of every network with a deployment feed and announce the new tokens
+pub fn spawn_announcer(state: Arc<BotState>, http: Arc<Http>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.deployment_interval);
+        loop {
+            ticker.tick().await;
+            let feeds = match state.storage.deployment_feeds(None).await {
+                Ok(feeds) => feeds,
+                Err(e) => {
+                    println!("Failed to list the deployment feeds: {}", e);
+                    continue;
+                },
+            };
+
+            let networks: BTreeSet<&str> = feeds.iter().map(|feed| feed.network.as_str()).collect();
+            for network_name in networks {
+                let network = match state.networks.get(network_name) {
+                    Some(network) => network,
+                    None => continue, // The network was removed from networks.json
+                };
+                let deployments = match new_deployments(&state, network).await {
+                    Ok(deployments) => deployments,
+                    Err(e) => {
+                        println!("Failed to check the new deployments on {}: {}", network_name, e);
+                        continue;
+                    },
+                };
+
+                for token in &deployments {
+                    let deployer = deployer(network, token).await;
+                    for feed in feeds.iter().filter(|feed| feed.network == network.name && passes(feed, token)) {
+                        announce(&state, &http, network, token, deployer.as_deref(), feed).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+// Tokens deployed since the last run, oldest first. The list is newest first, so paging stops at the
+// first token that was already seen. The first run on a network only remembers where the list starts.
+async fn new_deployments(state: &BotState, network: &Network) -> Result<Vec<ResultStruct>, KasplexError> {
+    let seen = match state.storage.deployment_cursor(&network.name).await {
+        Ok(seen) => seen,
+        Err(e) => {
+            println!("Failed to read the deployment cursor of {}: {}", network.name, e);
+            return Ok(Vec::new());
+        },
+    };
+
+    let mut deployments = Vec::new();
+    let mut cursor: Option<Cursor> = None;
+    for _ in 0..MAX_PAGES {
+        let page = network.client.token_list(cursor.as_ref()).await?;
+        cursor = page.next_cursor();
+        let count = page.result.len();
+        // Stopping at the first seen token is only safe while the API keeps listing newest first
+        let newest_first = page.result.windows(2).all(|pair| op_score(&pair[0]) >= op_score(&pair[1]));
+        if !newest_first {
+            println!("The token list of {} is not sorted newest first, reading on past seen tokens", network.name);
+        }
+        let fresh: Vec<ResultStruct> = page.result.into_iter()
+            .filter(|token| !matches!(seen, Some(seen) if op_score(token) <= seen))
+            .collect();
+        let reached_seen = newest_first && fresh.len() < count;
+        deployments.extend(fresh);
+
+        if seen.is_none() || reached_seen || cursor.is_none() {
+            break;
+        }
+    }
+
+    let newest = deployments.iter().map(op_score).max();
+    if let Some(newest) = newest {
+        // Saved before announcing so a crash halfway never announces a token twice
+        if let Err(e) = state.storage.set_deployment_cursor(&network.name, newest, current_time()).await {
+            println!("Failed to save the deployment cursor of {}: {}", network.name, e);
+            return Ok(Vec::new());
+        }
+    }
+    if seen.is_none() {
+        return Ok(Vec::new());
+    }
+
+    deployments.sort_by_key(op_score);
+    Ok(deployments)
+}
+
+fn op_score(token: &ResultStruct) -> u128 {
+    token.opScoreAdd.parse().unwrap_or(0)
+}
+
+// Address that sent the deploy operation
+async fn deployer(network: &Network, token: &ResultStruct) -> Option<String> {
+    match network.client.op(&token.hashRev).await {
+        Ok(operation) if !operation.from.is_empty() => Some(operation.from),
+        Ok(_) => None,
+        Err(e) => {
+            println!("Failed to look up the deployer of {}: {}", token.tick, e);
+            None
+        },
+    }
+}
+
+// Whether a token is large enough and pre-allocates little enough for a feed
+fn passes(feed: &DeploymentFeed, token: &ResultStruct) -> bool {
+    let Ok(decimals) = TokenAmount::parse_decimals(&token.dec) else {
+        return false;
+    };
+    let Ok(max) = TokenAmount::parse(&token.max, decimals) else {
+        return false;
+    };
+    if max.raw() < feed.min_supply.saturating_mul(10u128.pow(decimals)) {
+        return false;
+    }
+
+    // Compared on the exact amounts, the limit is in hundredths of a percent. An empty pre means nothing was
+    // pre-allocated, a malformed one cannot be shown to stay under the limit.
+    let pre = match token.pre.trim().is_empty() {
+        true => Ok(TokenAmount::zero(decimals)),
+        false => TokenAmount::parse(&token.pre, decimals),
+    };
+    match (feed.max_pre, pre) {
+        (None, _) => true,
+        (Some(limit), Ok(pre)) => pre.raw().saturating_mul(10_000) <= max.raw().saturating_mul(limit as u128),
+        (Some(_), Err(_)) => false,
+    }
+}
+
+async fn announce(state: &BotState, http: &Http, network: &Network, token: &ResultStruct, deployer: Option<&str>, feed: &DeploymentFeed) {
+    let guild_id = GuildId::new(feed.guild_id);
+    let settings = state.guild_settings.get(Some(guild_id));
+    let format = number_format(state, Some(guild_id), false);
+    let reply = settings.theme_reply(deployment_reply(network, &format, token, deployer));
+
+    let channel_id = ChannelId::new(feed.channel_id);
+    if let Err(why) = channel_id.send_message(http, reply.into_message()).await {
+        println!("Failed to announce {} to {}: {:?}", token.tick, channel_id, why);
+    }
+}
+
+fn deployment_reply(network: &Network, format: &NumberFormat, token: &ResultStruct, deployer: Option<&str>) -> Reply {
+    let amounts = TokenAmounts::new(&token.tick, &token.dec);
+    let max = amounts.parse(&token.max);
+    let pre = amounts.parse_pre(&token.pre);
+
+    // Show the share of the supply next to a pre-allocation
+    let mut pre_allocation = format_pre_allocation(format, &pre);
+    if let (Ok(pre), Ok(max)) = (&pre, &max) {
+        if let Some(share) = pre.percent_of(max).filter(|_| !pre.is_zero()) {
+            pre_allocation = format!("{} ({})", pre_allocation, format.percent(share));
+        }
+    }
+
+    let mut description = format!("Network: **{}**", network.label);
+    if let Ok(deployed_ms) = token.mtsAdd.parse::<u64>() {
+        description = format!("{}\nDeployed <t:{}:R>", description, deployed_ms / 1000);
+    }
+
+    Reply::new().embed(theme::current().embed()
+        .title(format!("🆕 {} was deployed", token.tick.to_uppercase()))
+        .description(description)
+        .field("Max Supply", format.amount_or_na(&max, Field::Supply), true)
+        .field("Limit", format.amount_or_na(&amounts.parse(&token.lim), Field::Supply), true)
+        .field("Pre-Allocation", pre_allocation, true)
+        .field("Deployer", deployer.map(|address| network.address_link(address)).unwrap_or_else(|| "N/A".to_string()), false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(min_supply: u128, max_pre: Option<u32>) -> DeploymentFeed {
+        DeploymentFeed { channel_id: 1, guild_id: 1, network: "mainnet".to_string(), min_supply, max_pre }
+    }
+
+    // A token with 8 decimals
+    fn token(max: &str, pre: &str) -> ResultStruct {
+        ResultStruct { tick: "TEST".to_string(), max: max.to_string(), pre: pre.to_string(), dec: "8".to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn min_supply_is_in_whole_tokens() {
+        let feed = feed(1_000, None);
+        assert!(passes(&feed, &token("100000000000", "")));
+        assert!(!passes(&feed, &token("99999999999", "")));
+    }
+
+    #[test]
+    fn max_pre_compares_exactly() {
+        let limited = feed(0, Some(250)); // 2.5%
+        assert!(passes(&limited, &token("100000000000", "")));
+        assert!(passes(&limited, &token("100000000000", "2500000000")));
+        assert!(!passes(&limited, &token("100000000000", "2500000001")));
+        // Without a limit the pre-allocation does not matter
+        assert!(passes(&feed(0, None), &token("100000000000", "100000000000")));
+    }
+
+    #[test]
+    fn malformed_fields_do_not_pass() {
+        let unfiltered = feed(0, None);
+        assert!(!passes(&unfiltered, &token("lots", "")));
+        assert!(!passes(&unfiltered, &ResultStruct { dec: "x".to_string(), ..token("100", "") }));
+
+        // A malformed pre only matters to a feed that limits it
+        assert!(passes(&unfiltered, &token("100000000000", "n/a")));
+        assert!(!passes(&feed(0, Some(10_000)), &token("100000000000", "n/a")));
+    }
+}
@@ -0,0 +1,235 @@
+// src/format.rs
+use crate::amount::{AmountError, Percent, TokenAmount};
+
+// Power of ten and suffix of each compact unit, largest first; the last entry is for amounts below a thousand
+const UNITS: [(u32, &str); 6] = [(15, "Q"), (12, "T"), (9, "B"), (6, "M"), (3, "K"), (0, "")];
+
+// Thousands and decimal separators of a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub name: &'static str,
+    group: &'static str,
+    decimal: &'static str,
+}
+
+const LOCALES: [Locale; 4] = [
+    Locale { name: "en", group: ",", decimal: "." },       // 1,234,567.89
+    Locale { name: "de", group: ".", decimal: "," },       // 1.234.567,89
+    Locale { name: "fr", group: "\u{202f}", decimal: "," }, // 1 234 567,89
+    Locale { name: "ch", group: "'", decimal: "." },       // 1'234'567.89
+];
+
+impl Locale {
+    pub const NAMES: &'static [&'static str] = &["en", "de", "fr", "ch"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        LOCALES.iter().find(|locale| locale.name == name).copied()
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        LOCALES[0]
+    }
+}
+
+// What a number shows, each kind of field gets its own number of significant digits in compact mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Supply,  // Max supply, limit, minted and pre-allocation
+    Holding, // Amounts held by the top holders
+    Balance, // A wallet's balance, shown a little more precisely
+}
+
+impl Field {
+    fn digits(&self) -> u32 {
+        match self {
+            Field::Supply => 4,
+            Field::Holding => 4,
+            Field::Balance => 6,
+        }
+    }
+}
+
+// How numbers are written in a reply: the guild's locale, compact (1.235M) or exact (1,234,567.891)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NumberFormat {
+    pub locale: Locale,
+    pub exact: bool,
+}
+
+impl NumberFormat {
+    pub fn new(locale: Locale, exact: bool) -> Self {
+        NumberFormat { locale, exact }
+    }
+
+    // A token amount, compact with the field's significant digits or every decimal in exact mode
+    pub fn amount(&self, amount: &TokenAmount, field: Field) -> String {
+        if self.exact {
+            self.localize(&amount.to_string())
+        } else {
+            self.compact(amount, field.digits())
+        }
+    }
+
+    // Same for an amount that may have failed to parse, which shows as N/A. The failure was logged where it was parsed.
+    pub fn amount_or_na(&self, amount: &Result<TokenAmount, AmountError>, field: Field) -> String {
+        match amount {
+            Ok(amount) => self.amount(amount, field),
+            Err(_) => "N/A".to_string(),
+        }
+    }
+
+    pub fn percent(&self, percent: Percent) -> String {
+        let hundredths = percent.hundredths();
+        format!("{}%", self.localize(&format!("{}.{:02}", hundredths / 100, hundredths % 100)))
+    }
+
+    // A whole number such as a holder count, e.g. 12,345
+    pub fn count(&self, count: u128) -> String {
+        self.localize(&count.to_string())
+    }
+
+    // A count the API sent as text; anything that is not a number is shown as it came
+    pub fn count_text(&self, count: &str) -> String {
+        match count.trim().parse::<u128>() {
+            Ok(count) => self.count(count),
+            Err(_) => count.to_string(),
+        }
+    }
+
+    // A measured value such as a rate or a coefficient with a fixed number of decimals
+    pub fn decimal(&self, value: f64, decimals: usize) -> String {
+        self.localize(&format!("{:.*}", decimals, value))
+    }
+
+    // Pick the largest unit the amount reaches and keep `digits` significant digits,
+    // moving up a unit when rounding reaches a thousand (999.96K becomes 1M)
+    fn compact(&self, amount: &TokenAmount, digits: u32) -> String {
+        let whole = amount.raw() / 10u128.pow(amount.decimals());
+        let mut unit = UNITS.iter().position(|(power, _)| whole >= 10u128.pow(*power)).unwrap_or(UNITS.len() - 1);
+
+        loop {
+            let (power, suffix) = UNITS[unit];
+            let integer_digits = digit_count(whole / 10u128.pow(power));
+            let fraction_digits = if integer_digits > 0 {
+                digits.saturating_sub(integer_digits)
+            } else {
+                // Below one: skip the zeros after the decimal point before counting significant digits
+                let leading_zeros = amount.decimals().saturating_sub(digit_count(amount.raw()));
+                leading_zeros + digits
+            }.min(amount.decimals() + power); // The amount has no more digits than that
+
+            let scaled = amount.scaled(power, fraction_digits);
+            let scale = 10u128.pow(fraction_digits);
+            if scaled / scale >= 1000 && unit > 0 {
+                unit -= 1;
+                continue;
+            }
+
+            let mut plain = (scaled / scale).to_string();
+            if fraction_digits > 0 {
+                let fraction = format!("{:0width$}", scaled % scale, width = fraction_digits as usize);
+                let fraction = fraction.trim_end_matches('0');
+                if !fraction.is_empty() {
+                    plain = format!("{}.{}", plain, fraction);
+                }
+            }
+            return format!("{}{}", self.localize(&plain), suffix);
+        }
+    }
+
+    // Put the locale's separators into a plain number such as `1234567.89`
+    fn localize(&self, plain: &str) -> String {
+        let (sign, unsigned) = match plain.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", plain),
+        };
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let mut grouped = String::new();
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                grouped.push_str(self.locale.group);
+            }
+            grouped.push(digit);
+        }
+
+        match fraction {
+            Some(fraction) => format!("{}{}{}{}", sign, grouped, self.locale.decimal, fraction),
+            None => format!("{}{}", sign, grouped),
+        }
+    }
+}
+
+// Number of decimal digits, 0 for zero
+fn digit_count(value: u128) -> u32 {
+    match value {
+        0 => 0,
+        _ => value.ilog10() + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compact(raw: u128, decimals: u32, field: Field) -> String {
+        NumberFormat::default().amount(&TokenAmount::new(raw, decimals), field)
+    }
+
+    fn locale(name: &str, exact: bool) -> NumberFormat {
+        NumberFormat::new(Locale::parse(name).unwrap(), exact)
+    }
+
+    #[test]
+    fn keeps_the_significant_digits_of_each_field() {
+        assert_eq!(compact(1_234_567, 0, Field::Supply), "1.235M");
+        assert_eq!(compact(1_234_567, 0, Field::Balance), "1.23457M");
+        assert_eq!(compact(999, 0, Field::Supply), "999");
+        assert_eq!(compact(1_500, 0, Field::Supply), "1.5K");
+    }
+
+    #[test]
+    fn walks_every_unit_up_to_quadrillions() {
+        let units: Vec<String> = (0..=6).map(|power| compact(2 * 10u128.pow(power * 3), 0, Field::Supply)).collect();
+        assert_eq!(units, ["2", "2K", "2M", "2B", "2T", "2Q", "2,000Q"]);
+    }
+
+    #[test]
+    fn moves_up_a_unit_when_rounding_reaches_a_thousand() {
+        assert_eq!(compact(999_960, 0, Field::Supply), "1M");
+        assert_eq!(compact(999_940, 0, Field::Supply), "999.9K");
+    }
+
+    #[test]
+    fn shows_amounts_below_one() {
+        assert_eq!(compact(0, 8, Field::Supply), "0");
+        assert_eq!(compact(123_456, 9, Field::Supply), "0.0001235");
+        assert_eq!(compact(1, 18, Field::Balance), "0.000000000000000001");
+        assert_eq!(compact(1_500_000_000_000_000_000, 18, Field::Supply), "1.5");
+    }
+
+    #[test]
+    fn writes_exact_amounts_with_the_locale_separators() {
+        let amount = TokenAmount::new(1_234_567_891, 3);
+        assert_eq!(locale("en", true).amount(&amount, Field::Supply), "1,234,567.891");
+        assert_eq!(locale("de", true).amount(&amount, Field::Supply), "1.234.567,891");
+        assert_eq!(locale("ch", true).amount(&amount, Field::Supply), "1'234'567.891");
+        assert_eq!(locale("fr", false).amount(&amount, Field::Supply), "1,235M");
+    }
+
+    #[test]
+    fn formats_percentages_counts_and_missing_values() {
+        let third = TokenAmount::new(1, 0).percent_of(&TokenAmount::new(3, 0)).unwrap();
+        assert_eq!(locale("en", false).percent(third), "33.33%");
+        assert_eq!(locale("de", false).percent(third), "33,33%");
+        assert_eq!(locale("en", false).count_text(" 12345 "), "12,345");
+        assert_eq!(locale("en", false).count_text("many"), "many");
+        assert_eq!(NumberFormat::default().amount_or_na(&TokenAmount::parse("x", 0), Field::Supply), "N/A");
+    }
+}
@@ -0,0 +1,126 @@
+// src/guild_settings.rs
+use crate::imports::*;
+use crate::mint_status::milestones::QuietHours;
+use crate::reply::Reply;
+use serenity::builder::CreateEmbedFooter;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+const SETTINGS_FILE: &str = "data_storage/guild_settings.json"; // Per-guild settings, keyed by guild id
+
+// Settings a guild's admins can change
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuildSettings {
+    pub prefix: Option<String>,  // Prefix for message commands instead of `!`
+    pub network: Option<String>, // Network used when a command has no `--net`
+    pub ticker: Option<String>,  // Ticker used when a token command is run without one
+    pub channels: Vec<u64>,      // Channels the bot answers in, every channel when empty
+    pub locale: Option<String>,  // Thousands and decimal separators, e.g. `de` for 1.234,5
+    pub color: Option<u32>,      // Embed colour instead of the theme's
+    pub footer: Option<String>,  // Embed footer text instead of the theme's
+    pub rules: BTreeMap<String, CommandRule>, // Who may use a command and where, keyed by command name
+    pub quiet_hours: Option<QuietHours>, // Hours (UTC) in which alerts wait instead of the bot's default
+}
+
+// Limits on one command; an empty list does not limit anything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandRule {
+    pub channels: Vec<u64>, // Channels the command may be used in
+    pub roles: Vec<u64>,    // Roles whose members may use it
+    pub users: Vec<u64>,    // Users who may use it, in addition to the roles
+}
+
+impl CommandRule {
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty() && self.roles.is_empty() && self.users.is_empty()
+    }
+
+    pub fn allows_channel(&self, channel_id: ChannelId) -> bool {
+        self.channels.is_empty() || self.channels.contains(&channel_id.get())
+    }
+
+    // Anyone may use the command until roles or users are set, then only those
+    pub fn allows_member(&self, user_id: UserId, roles: &[RoleId]) -> bool {
+        (self.roles.is_empty() && self.users.is_empty())
+            || self.users.contains(&user_id.get())
+            || roles.iter().any(|role| self.roles.contains(&role.get()))
+    }
+}
+
+impl GuildSettings {
+    // Whether commands may be used in this channel
+    pub fn allows_channel(&self, channel_id: ChannelId) -> bool {
+        self.channels.is_empty() || self.channels.contains(&channel_id.get())
+    }
+
+    // Apply the guild's colour and footer to every embed of a reply
+    pub fn theme_reply(&self, mut reply: Reply) -> Reply {
+        reply.embeds = reply.embeds.into_iter()
+            .map(|embed| {
+                let embed = match self.color {
+                    Some(color) => embed.color(color),
+                    None => embed,
+                };
+                match &self.footer {
+                    Some(footer) => embed.footer(CreateEmbedFooter::new(footer)),
+                    None => embed,
+                }
+            })
+            .collect();
+        reply
+    }
+}
+
+// In-memory copy of the guild settings, written back to disk on every change
+#[derive(Debug, Default)]
+pub struct GuildSettingsStore {
+    settings: RwLock<HashMap<u64, GuildSettings>>,
+    saving: tokio::sync::Mutex<()>, // Saves run one at a time so an older copy never overwrites a newer one
+}
+
+impl GuildSettingsStore {
+    // Load the saved settings; a missing file means no guild has changed anything yet
+    pub fn load() -> io::Result<Self> {
+        let settings = match std::fs::read_to_string(SETTINGS_FILE) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(GuildSettingsStore { settings: RwLock::new(settings), ..Default::default() })
+    }
+
+    // Settings for a guild, or the defaults outside of guilds
+    pub fn get(&self, guild_id: Option<GuildId>) -> GuildSettings {
+        guild_id
+            .and_then(|guild_id| self.settings.read().unwrap().get(&guild_id.get()).cloned())
+            .unwrap_or_default()
+    }
+
+    // Change a guild's settings and save them. The change is made on the current settings under the lock,
+    // so concurrent edits both stick; the file is written after the lock is released.
+    pub async fn update<T>(&self, guild_id: GuildId, change: impl FnOnce(&mut GuildSettings) -> T) -> io::Result<T> {
+        let _saving = self.saving.lock().await;
+        let (result, settings) = {
+            let mut settings = self.settings.write().unwrap();
+            let result = change(settings.entry(guild_id.get()).or_default());
+            (result, settings.clone())
+        };
+
+        tokio::task::spawn_blocking(move || save(&settings))
+            .await
+            .map_err(io::Error::other)??;
+        Ok(result)
+    }
+}
+
+fn save(settings: &HashMap<u64, GuildSettings>) -> io::Result<()> {
+    create_dir_all("data_storage")?;
+    let json = serde_json::to_string_pretty(settings)?;
+    // Write to a temporary file first so a crash never leaves half a settings file behind
+    let temp_file = format!("{}.tmp", SETTINGS_FILE);
+    std::fs::write(&temp_file, json)?;
+    std::fs::rename(temp_file, SETTINGS_FILE)
+}
@@ -0,0 +1,184 @@
+// src/holder_status/analytics.rs
+use crate::amount::TokenAmount;
+use crate::format::NumberFormat;
+use crate::result_struct::Holder;
+
+const BUCKETS: [usize; 3] = [1, 10, 50]; // Top holder groups whose share of the minted supply is shown
+const CONTROL_SHARE: f64 = 51.0; // Share of the supply the Nakamoto coefficient counts up to
+
+// Concentration levels that earn a warning in the embed
+#[derive(Debug, Clone)]
+pub struct ConcentrationThresholds {
+    pub top10_share: f64, // % of the minted supply held by the top 10
+    pub gini: f64,        // Gini coefficient between 0 and 1
+    pub nakamoto: usize,  // Holders needed to control 51%, at or below this is a warning
+    pub hhi: f64,         // Herfindahl index between 0 and 10,000
+}
+
+// Concentration measures for a token, computed from the top holders the API returns
+#[derive(Debug, Clone)]
+pub struct HolderAnalytics {
+    pub holders: usize,                // Holders in the list the measures are based on
+    pub bucket_shares: Vec<(usize, f64)>, // Top N and their % of the minted supply
+    pub gini: f64,
+    pub nakamoto: Option<usize>,       // None when the listed holders together stay below 51%
+    pub hhi: f64,
+}
+
+// Work out the concentration measures; None without holders or minted supply
+pub fn analyze(holders: &[Holder], minted: u128) -> Option<HolderAnalytics> {
+    if holders.is_empty() || minted == 0 {
+        return None;
+    }
+
+    // Decimals cancel out in the shares, so the raw amounts are compared as they are
+    let mut raw: Vec<TokenAmount> = holders.iter()
+        .map(|holder| TokenAmount::parse(&holder.amount, 0).unwrap_or(TokenAmount::zero(0)))
+        .collect();
+    raw.sort_by_key(|amount| std::cmp::Reverse(amount.raw())); // Largest first
+    let minted = TokenAmount::new(minted, 0);
+
+    let amounts: Vec<f64> = raw.iter().map(|amount| amount.raw() as f64).collect();
+    let shares: Vec<f64> = amounts.iter().map(|amount| amount / minted.raw() as f64 * 100.0).collect();
+
+    // The bucket sums are exact, the other measures only need to be close
+    let bucket_shares = BUCKETS.iter()
+        .filter(|size| **size == 1 || shares.len() > **size / 2) // Skip buckets the list barely covers
+        .map(|size| {
+            let share = TokenAmount::sum(0, raw.iter().take(*size)).percent_of(&minted);
+            (*size, share.map(|share| share.as_f64()).unwrap_or(0.0))
+        })
+        .collect();
+
+    let mut cumulative = 0.0;
+    let nakamoto = shares.iter().position(|share| {
+        cumulative += share;
+        cumulative >= CONTROL_SHARE
+    }).map(|index| index + 1);
+
+    Some(HolderAnalytics {
+        holders: shares.len(),
+        bucket_shares,
+        gini: gini(&amounts),
+        nakamoto,
+        hhi: shares.iter().map(|share| share * share).sum(),
+    })
+}
+
+// Gini coefficient of the amounts: 0 when everyone holds the same, close to 1 when one holder has everything
+fn gini(amounts: &[f64]) -> f64 {
+    let total: f64 = amounts.iter().sum();
+    if amounts.len() < 2 || total == 0.0 {
+        return 0.0;
+    }
+
+    let mut ascending = amounts.to_vec();
+    ascending.sort_by(|a, b| a.total_cmp(b));
+    let n = ascending.len() as f64;
+    let weighted: f64 = ascending.iter().enumerate().map(|(index, amount)| (index as f64 + 1.0) * amount).sum();
+    (2.0 * weighted) / (n * total) - (n + 1.0) / n
+}
+
+// Lines of the "Distribution" embed field, with a warning on every measure past its threshold
+pub fn distribution_lines(analytics: &HolderAnalytics, thresholds: &ConcentrationThresholds, format: &NumberFormat) -> Vec<String> {
+    let warn = |crossed: bool| if crossed { " ⚠️" } else { "" };
+
+    let mut lines: Vec<String> = analytics.bucket_shares.iter()
+        .map(|(size, share)| {
+            let label = if *size == 1 { "Top holder".to_string() } else { format!("Top {}", size) };
+            format!("{}: {}%{}", label, format.decimal(*share, 2), warn(*size == 10 && *share >= thresholds.top10_share))
+        })
+        .collect();
+
+    lines.push(format!("Gini (top {}): {}{}", analytics.holders, format.decimal(analytics.gini, 2), warn(analytics.gini >= thresholds.gini)));
+    lines.push(match analytics.nakamoto {
+        Some(count) => format!("Nakamoto: {} holder{} control 51%{}", count, if count == 1 { "" } else { "s" }, warn(count <= thresholds.nakamoto)),
+        None => format!("Nakamoto: more than {} holders", analytics.holders),
+    });
+    lines.push(format!("HHI: {}{}", format.decimal(analytics.hhi, 0), warn(analytics.hhi >= thresholds.hhi)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holders(amounts: &[u128]) -> Vec<Holder> {
+        amounts.iter().enumerate()
+            .map(|(index, amount)| Holder { address: format!("kaspa:holder{}", index), amount: amount.to_string() })
+            .collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn needs_holders_and_a_minted_supply() {
+        assert!(analyze(&[], 100).is_none());
+        assert!(analyze(&holders(&[10]), 0).is_none());
+    }
+
+    #[test]
+    fn a_single_holder_controls_everything() {
+        let analytics = analyze(&holders(&[100]), 100).unwrap();
+        assert_eq!(analytics.bucket_shares, vec![(1, 100.0)]); // Too few holders for the top 10 and 50
+        assert_close(analytics.gini, 0.0);
+        assert_eq!(analytics.nakamoto, Some(1));
+        assert_close(analytics.hhi, 10_000.0);
+    }
+
+    #[test]
+    fn an_equal_distribution_has_no_inequality() {
+        let analytics = analyze(&holders(&[10; 10]), 100).unwrap();
+        assert_eq!(analytics.bucket_shares, vec![(1, 10.0), (10, 100.0)]);
+        assert_close(analytics.gini, 0.0);
+        assert_eq!(analytics.nakamoto, Some(6)); // 60% is the first share past 51%
+        assert_close(analytics.hhi, 1_000.0);
+    }
+
+    #[test]
+    fn one_whale_among_small_holders() {
+        // Listed in no particular order, the largest amount is found wherever it is
+        let analytics = analyze(&holders(&[1, 1, 1, 91, 1, 1, 1, 1, 1, 1]), 100).unwrap();
+        assert_eq!(analytics.bucket_shares, vec![(1, 91.0), (10, 100.0)]);
+        assert_close(analytics.gini, 0.81);
+        assert_eq!(analytics.nakamoto, Some(1));
+        assert_close(analytics.hhi, 8_290.0);
+    }
+
+    #[test]
+    fn shares_are_of_the_minted_supply_not_of_the_listed_holders() {
+        let analytics = analyze(&holders(&[30, 10, 0]), 200).unwrap();
+        assert_eq!(analytics.bucket_shares, vec![(1, 15.0)]);
+        assert_eq!(analytics.nakamoto, None); // The listed holders only have 20%
+        assert_close(analytics.hhi, 250.0);
+    }
+
+    #[test]
+    fn malformed_amounts_count_as_zero() {
+        let mut listed = holders(&[50, 50]);
+        listed.push(Holder { address: "kaspa:broken".to_string(), amount: "lots".to_string() });
+        let analytics = analyze(&listed, 100).unwrap();
+        assert_eq!(analytics.holders, 3);
+        assert_eq!(analytics.nakamoto, Some(2));
+        assert_close(analytics.hhi, 5_000.0);
+    }
+
+    #[test]
+    fn warns_about_every_measure_past_its_threshold() {
+        let thresholds = ConcentrationThresholds { top10_share: 50.0, gini: 0.8, nakamoto: 3, hhi: 2500.0 };
+        let analytics = analyze(&holders(&[1, 1, 1, 91, 1, 1, 1, 1, 1, 1]), 100).unwrap();
+        let lines = distribution_lines(&analytics, &thresholds, &NumberFormat::default());
+        assert_eq!(lines, [
+            "Top holder: 91.00%",
+            "Top 10: 100.00% ⚠️",
+            "Gini (top 10): 0.81 ⚠️",
+            "Nakamoto: 1 holder control 51% ⚠️",
+            "HHI: 8,290 ⚠️",
+        ]);
+
+        let calm = distribution_lines(&analyze(&holders(&[10; 10]), 1_000).unwrap(), &thresholds, &NumberFormat::default());
+        assert!(calm.iter().all(|line| !line.contains('⚠')), "{:?}", calm);
+    }
+}
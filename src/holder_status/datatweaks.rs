@@ -1,95 +1,77 @@
 //use crate::DataStruct; // Import the DataStruct from the current crate
-use crate::imports::*; // Import everything from the imports module
-use serde_json::Value;
-use std::fs;
-use rand::distributions::{Distribution, WeightedIndex};
-use rand::thread_rng;
-use serenity::builder::CreateEmbedFooter;
+use crate::amount::TokenAmounts;
+use crate::cache::{self, Cache, CacheKey, Cached, Endpoint};
+use crate::format::{Field, NumberFormat};
+use crate::kasplex::{KasplexError, Page};
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::result_struct::TokenInfo;
+use crate::theme;
 //use chrono::{Utc, TimeZone};
 
-// Define a struct for handling holder data
-#[derive(Debug, Serialize, Deserialize)]
-pub struct HolderData {
-    pub message: String,
-    pub prev: String,
-    pub next: String,
-    pub result: Vec<TokenInfo>,
-}
+const MAX_PAGES: usize = 20; // A token list longer than this is refused rather than shown or compared in part
+const MAX_TOKEN_FIELDS: usize = 23; // Leaves room for the address and the "more" field within Discord's 25
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TokenInfo {
-    pub tick: String,
-    pub balance: String,
-    pub locked: String,
-    pub dec: String,
-    pub opScoreMod: String,
-}
+// Token balances held by a wallet address, every page of /address/{addr}/tokenlist joined into one
+pub type HolderData = Page<TokenInfo>;
 
-// Helper function to select a random background image based on weight
-fn select_random_banner(banners: &Vec<Value>) -> &str {
-    let weights: Vec<_> = banners.iter().map(|b| b["weight"].as_u64().unwrap_or(1) as u32).collect();
-    let dist = WeightedIndex::new(&weights).unwrap();
-    let mut rng = thread_rng();
-    let index = dist.sample(&mut rng);
-    banners[index]["url"].as_str().unwrap()
+// Fetch the balances for the given wallet address through the cache
+pub async fn fetch_holder_data(cache: &dyn Cache, network: &Network, address: &str) -> Result<Cached<HolderData>, KasplexError> {
+    let key = CacheKey::new(&network.name, Endpoint::AddressBalances, address);
+    cache::get_or_fetch(cache, key, || fetch_all_pages(network, address)).await
 }
 
-// Helper function to format large numbers
-fn format_large_number(number: f64) -> String {
-    const BILLION: f64 = 1_000_000_000.0;
-    const MILLION: f64 = 1_000_000.0;
-    const THOUSAND: f64 = 1_000.0;
-
-    if number >= BILLION {
-        format!("{:.2}B", number / BILLION)
-    } else if number >= MILLION {
-        format!("{:.2}M", number / MILLION)
-    } else if number >= THOUSAND {
-        format!("{:.2}K", number / THOUSAND)
-    } else {
-        format!("{}", number)
+// Follow the `next` cursor to the end of the list, so a token on a later page is neither missed
+// nor seen to disappear when it moves between pages
+async fn fetch_all_pages(network: &Network, address: &str) -> Result<HolderData, KasplexError> {
+    let mut page = network.client.address_token_list(address, None).await?;
+    let mut cursor = page.next_cursor();
+    for _ in 1..MAX_PAGES {
+        let Some(next) = cursor.take() else {
+            page.next = None;
+            return Ok(page);
+        };
+        let more = network.client.address_token_list(address, Some(&next)).await?;
+        cursor = more.next_cursor().filter(|following| *following != next);
+        page.result.extend(more.result);
+    }
+    match cursor {
+        None => {
+            page.next = None;
+            Ok(page)
+        },
+        Some(_) => Err(KasplexError::Malformed(format!("the token list of {} has more than {} pages", address, MAX_PAGES))),
     }
-}
-
-// Fetch data from the API for the given wallet address
-pub async fn fetch_holder_data(api_base_url: &str, address: &str) -> Result<HolderData, Error> {
-    let url = format!("{}/address/{}/tokenlist", api_base_url, address); // Construct the API URL using the base URL
-    let response = reqwest::get(url).await?.json::<HolderData>().await?; // Send a GET request and parse the JSON response
-    Ok(response)
 }
 
 // Format the fetched holder data into a message to be sent
-pub async fn format_holder_data(data: HolderData, address: &str) -> CreateMessage {
-    // Load the JSON template
-    let template_content = fs::read_to_string("message_template.json").expect("Failed to read message template");
-    let template: Value = serde_json::from_str(&template_content).expect("Failed to parse message template");
-
-    // Ensure required fields are present in the template
-    let color = template["color"].as_u64().expect("Color not found in message template") as u32;
-    let background_images = template["background_images"].as_array().expect("Background images not found in message template");
-    let background_image_url = select_random_banner(background_images);
-    let author_name = template["author"]["name"].as_str().expect("Author name not found in message template");
-    let author_icon_url = template["author"]["icon_url"].as_str().expect("Author icon URL not found in message template");
+pub async fn format_holder_data(network: &Network, format: &NumberFormat, data: Cached<HolderData>, address: &str) -> Reply {
+    let mut description = format!("Network: **{}**", network.label);
+    if data.stale {
+        description.push_str("\n⚠️ The Kasplex API is unavailable, balances may be stale.");
+    }
 
     // Create the message payload with an embedded message
-    let mut embed = CreateEmbed::new()
-        .color(color)
-        .image(background_image_url)
-        .author(CreateEmbedAuthor::new(author_name).icon_url(author_icon_url))
-        .footer(CreateEmbedFooter::new("x.com/coinchimpx"));
+    let mut embed = theme::current().embed()
+        .description(description);
 
     // Add content and footer
-    let content = format!("**# KRC20 Balance**");
+    let content = "**# KRC20 Balance**";
     // Format each token holding information
     embed = embed.field("Address", address ,false);
 
-    for token in data.result.iter() {
-        let balance = token.balance.parse::<f64>().expect("Not a valid f64") / 10f64.powf(token.dec.parse::<f64>().expect("Not a valid f64"));
-        let formatted_balance = format_large_number(balance);
+    let tokens = &data.value.result;
+    let shown = if tokens.len() > MAX_TOKEN_FIELDS + 1 { MAX_TOKEN_FIELDS } else { tokens.len() };
+    for token in tokens.iter().take(shown) {
+        let balance = TokenAmounts::new(&token.tick, &token.dec).parse(&token.balance);
+        let formatted_balance = format.amount_or_na(&balance, Field::Balance);
         embed = embed.field(token.tick.to_uppercase(), formatted_balance, true);
     }
+    if tokens.len() > shown {
+        embed = embed.field("More tokens", format!("…and {} more", tokens.len() - shown), false);
+    }
 
-    CreateMessage::new()
+    Reply::new()
         .content(content)
         .embed(embed)
 }
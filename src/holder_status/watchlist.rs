@@ -0,0 +1,238 @@
+// src/holder_status/watchlist.rs
+use crate::imports::*;
+use crate::amount::TokenAmount;
+use crate::cache::current_time;
+use crate::format::{Field, NumberFormat};
+use crate::holder_status::datatweaks;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::result_struct::TokenInfo;
+use crate::state::BotState;
+use crate::storage::WatchedAddress;
+use crate::theme;
+use serenity::http::Http;
+use serenity::model::id::UserId;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const MAX_FIELDS: usize = 25; // Discord's limit for fields in one embed
+const MAX_FIELD_VALUE: usize = 1024; // Discord's limit for the text of one field
+
+// One token whose balance or locked amount moved between two checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BalanceChange {
+    tick: String,
+    decimals: u32,
+    balance: (u128, u128), // Before and after, raw
+    locked: (u128, u128),
+}
+
+// Check the watched addresses that are due and DM their owners about any change
+pub fn spawn_watcher(state: Arc<BotState>, http: Arc<Http>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.watch_interval);
+        loop {
+            ticker.tick().await;
+            let watches = match state.storage.watched_addresses(None).await {
+                Ok(watches) => watches,
+                Err(e) => {
+                    println!("Failed to list the watched addresses: {}", e);
+                    continue;
+                },
+            };
+
+            let now = current_time();
+            for watch in watches.iter().filter(|watch| watch.next_check <= now) {
+                let network = match state.networks.get(&watch.network) {
+                    Some(network) => network,
+                    None => continue, // The network was removed from networks.json
+                };
+                check(&state, &http, network, watch).await;
+            }
+        }
+    });
+}
+
+// Fetch the balances through the cache and compare them with the last check.
+// Failed checks back off exponentially, up to the configured maximum.
+async fn check(state: &BotState, http: &Http, network: &Network, watch: &WatchedAddress) {
+    let interval = state.config.watch_interval.as_secs();
+    let balances = match datatweaks::fetch_holder_data(state.cache.as_ref(), network, &watch.address).await {
+        Ok(data) if !data.stale => data.value.result,
+        result => {
+            if let Err(e) = result {
+                println!("Failed to check the watched address {}: {}", watch.address, e);
+            }
+            // Stale data is not compared, a change seen in it could be reported twice
+            let failures = watch.failures.saturating_add(1);
+            let backoff = interval.saturating_mul(2u64.saturating_pow(failures)).min(state.config.watch_max_backoff.as_secs());
+            if let Err(e) = state.storage.record_watch_check(watch, None, failures, current_time() + backoff).await {
+                println!("Failed to save the check of {}: {}", watch.address, e);
+            }
+            return;
+        },
+    };
+
+    let changes = match &watch.balances {
+        Some(previous) => balance_changes(previous, &balances),
+        None => Vec::new(), // The first check only records where the balances start
+    };
+
+    // Saved before the DM so a failed message is not sent again on the next check
+    if let Err(e) = state.storage.record_watch_check(watch, Some(balances), 0, current_time() + interval).await {
+        println!("Failed to save the check of {}: {}", watch.address, e);
+        return;
+    }
+    if changes.is_empty() {
+        return;
+    }
+
+    // DMs have no guild, so the numbers use the bot's default locale
+    let format = NumberFormat::new(state.config.default_locale, false);
+    let reply = changes_reply(network, &format, watch, &changes);
+    let user_id = UserId::new(watch.user_id);
+    if let Err(why) = user_id.direct_message(http, reply.into_message()).await {
+        println!("Failed to DM {} about {}: {:?}", user_id, watch.address, why);
+    }
+}
+
+// Tokens whose balance or locked amount differs, in ticker order. A token missing on one side counts as zero.
+fn balance_changes(previous: &[TokenInfo], current: &[TokenInfo]) -> Vec<BalanceChange> {
+    let mut changes: BTreeMap<String, BalanceChange> = BTreeMap::new();
+    let raw = |value: &str| value.trim().parse::<u128>().unwrap_or(0);
+
+    for (index, side) in [previous, current].into_iter().enumerate() {
+        for token in side {
+            let change = changes.entry(token.tick.to_uppercase()).or_insert_with(|| BalanceChange {
+                tick: token.tick.to_uppercase(),
+                decimals: TokenAmount::parse_decimals(&token.dec).unwrap_or(0),
+                balance: (0, 0),
+                locked: (0, 0),
+            });
+            let (balance, locked) = (raw(&token.balance), raw(&token.locked));
+            if index == 0 {
+                change.balance.0 = balance;
+                change.locked.0 = locked;
+            } else {
+                change.balance.1 = balance;
+                change.locked.1 = locked;
+            }
+        }
+    }
+
+    changes.into_values()
+        .filter(|change| change.balance.0 != change.balance.1 || change.locked.0 != change.locked.1)
+        .collect()
+}
+
+fn changes_reply(network: &Network, format: &NumberFormat, watch: &WatchedAddress, changes: &[BalanceChange]) -> Reply {
+    let name = match &watch.label {
+        Some(label) => format!("{} ({})", label, network.address_link(&watch.address)),
+        None => network.address_link(&watch.address),
+    };
+
+    let mut embed = theme::current().embed()
+        .title("Balance change")
+        .description(format!("{}\nNetwork: **{}**", name, network.label));
+
+    for (name, value) in change_fields(format, changes) {
+        embed = embed.field(name, value, true);
+    }
+
+    Reply::new().embed(embed)
+}
+
+// One field per token; past the field limit the last one names the tokens that did not fit
+fn change_fields(format: &NumberFormat, changes: &[BalanceChange]) -> Vec<(String, String)> {
+    let shown = match changes.len() > MAX_FIELDS {
+        true => MAX_FIELDS - 1,
+        false => changes.len(),
+    };
+
+    let mut fields: Vec<(String, String)> = changes[..shown].iter()
+        .map(|change| {
+            let mut lines = Vec::new();
+            if change.balance.0 != change.balance.1 {
+                lines.push(format!("Balance: {}", amount_with_delta(format, change.decimals, change.balance)));
+            }
+            if change.locked.0 != change.locked.1 {
+                lines.push(format!("Locked: {}", amount_with_delta(format, change.decimals, change.locked)));
+            }
+            (change.tick.clone(), lines.join("\n"))
+        })
+        .collect();
+
+    let rest = &changes[shown..];
+    if !rest.is_empty() {
+        let mut tickers = rest.iter().map(|change| change.tick.as_str()).collect::<Vec<_>>().join(", ");
+        if tickers.chars().count() > MAX_FIELD_VALUE {
+            tickers = format!("{}…", tickers.chars().take(MAX_FIELD_VALUE - 1).collect::<String>());
+        }
+        fields.push((format!("…and {} more changes", rest.len()), tickers));
+    }
+    fields
+}
+
+// The new amount followed by the signed difference, e.g. `1.25K (+250)`
+fn amount_with_delta(format: &NumberFormat, decimals: u32, (before, after): (u128, u128)) -> String {
+    let (sign, delta) = match after >= before {
+        true => ("+", after - before),
+        false => ("−", before - after),
+    };
+    format!(
+        "{} ({}{})",
+        format.amount(&TokenAmount::new(after, decimals), Field::Balance),
+        sign,
+        format.amount(&TokenAmount::new(delta, decimals), Field::Balance),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(tick: &str, balance: &str, locked: &str) -> TokenInfo {
+        TokenInfo { tick: tick.to_string(), balance: balance.to_string(), locked: locked.to_string(), dec: "2".to_string(), ..Default::default() }
+    }
+
+    fn change(tick: &str, before: u128, after: u128) -> BalanceChange {
+        BalanceChange { tick: tick.to_string(), decimals: 0, balance: (before, after), locked: (0, 0) }
+    }
+
+    #[test]
+    fn balance_changes_compare_both_checks() {
+        let previous = [balance("nacho", "100", "0"), balance("KASPER", "500", "0"), balance("GONE", "70", "0"), balance("SAME", "1", "1")];
+        let current = [balance("NACHO", "150", "0"), balance("KASPER", "500", "20"), balance("NEW", "9", "0"), balance("SAME", "1", "1")];
+
+        let changes = balance_changes(&previous, &current);
+        let expected = vec![
+            BalanceChange { tick: "GONE".to_string(), decimals: 2, balance: (70, 0), locked: (0, 0) },
+            BalanceChange { tick: "KASPER".to_string(), decimals: 2, balance: (500, 500), locked: (0, 20) },
+            BalanceChange { tick: "NACHO".to_string(), decimals: 2, balance: (100, 150), locked: (0, 0) },
+            BalanceChange { tick: "NEW".to_string(), decimals: 2, balance: (0, 9), locked: (0, 0) },
+        ];
+        assert_eq!(changes, expected);
+        assert!(balance_changes(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn change_fields_show_the_delta() {
+        let fields = change_fields(&NumberFormat::default(), &[change("NACHO", 1500, 1250)]);
+        assert_eq!(fields, vec![("NACHO".to_string(), "Balance: 1.25K (−250)".to_string())]);
+    }
+
+    #[test]
+    fn changes_past_the_field_limit_are_summed_up() {
+        let format = NumberFormat::default();
+        let changes: Vec<BalanceChange> = (0..MAX_FIELDS as u128).map(|index| change(&format!("T{:02}", index), 0, index + 1)).collect();
+        assert_eq!(change_fields(&format, &changes).len(), MAX_FIELDS);
+
+        let mut more = changes.clone();
+        more.push(change("T25", 0, 1));
+        more.push(change("T26", 0, 1));
+        let fields = change_fields(&format, &more);
+        assert_eq!(fields.len(), MAX_FIELDS);
+        assert_eq!(fields[MAX_FIELDS - 2].0, "T23");
+        assert_eq!(fields[MAX_FIELDS - 1], ("…and 3 more changes".to_string(), "T24, T25, T26".to_string()));
+    }
+}
@@ -0,0 +1,295 @@
+// src/holder_status/whales.rs
+use crate::imports::*;
+use crate::amount::TokenAmount;
+use crate::cache::current_time;
+use crate::commands::registry::number_format;
+use crate::format::{Field, NumberFormat};
+use crate::mint_status::datatweaks;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::result_struct::Holder;
+use crate::state::BotState;
+use crate::storage::{Subscription, SubscriptionKind, TokenSnapshot};
+use crate::theme;
+use serenity::http::Http;
+use serenity::model::id::GuildId;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+const NEW_ENTRY_RANK: usize = 10; // An address appearing in this many top holders is announced
+const MAX_LINES: usize = 15;      // Keeps one alert well within the embed size limit
+
+// A notable difference between two holder lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WhaleMove {
+    NewTopHolder { address: String, amount: u128, previous: String },
+    EnteredTop { address: String, rank: usize, amount: u128 },
+    BalanceChange { address: String, rank: usize, before: u128, after: u128 },
+}
+
+// Limits from the config for what counts as a whale move
+#[derive(Debug, Clone, Copy)]
+pub struct WhaleThresholds {
+    pub top_n: usize,    // Balance changes are watched among this many top holders
+    pub change_pct: f64, // Smallest balance change announced, in percent of the previous balance
+}
+
+// Compare the holder lists of the subscribed tokens every interval and post the moves
+pub fn spawn_scheduler(state: Arc<BotState>, http: Arc<Http>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.whale_interval);
+        loop {
+            ticker.tick().await;
+            let subscriptions = match state.storage.subscriptions(None, Some(SubscriptionKind::Whales)).await {
+                Ok(subscriptions) => subscriptions,
+                Err(e) => {
+                    println!("Failed to list the whale subscriptions: {}", e);
+                    continue;
+                },
+            };
+
+            let mut tokens: BTreeMap<(String, String), Vec<Subscription>> = BTreeMap::new();
+            for subscription in subscriptions {
+                tokens.entry((subscription.network.clone(), subscription.tick.clone())).or_default().push(subscription);
+            }
+
+            for ((network_name, tick), subscriptions) in tokens {
+                let network = match state.networks.get(&network_name) {
+                    Some(network) => network,
+                    None => continue, // The network was removed from networks.json
+                };
+                // Refreshing through the status path records a new snapshot once the cached holders expire
+                if let Err(e) = datatweaks::fetch_token(state.cache.as_ref(), &state.storage, network, &tick).await {
+                    println!("Failed to refresh the holders of {} on {}: {}", tick, network_name, e);
+                    continue;
+                }
+                let latest = match state.storage.latest_holder_snapshot(&network_name, &tick).await {
+                    Ok(Some(latest)) => latest,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        println!("Failed to read the latest snapshot of {}: {}", tick, e);
+                        continue;
+                    },
+                };
+                check_subscriptions(&state, &http, network, &latest, &subscriptions).await;
+            }
+        }
+    });
+}
+
+// Compare the latest snapshot with the one each channel saw last, loading every older snapshot once
+async fn check_subscriptions(state: &BotState, http: &Http, network: &Network, latest: &TokenSnapshot, subscriptions: &[Subscription]) {
+    let mut baselines: HashMap<i64, Option<TokenSnapshot>> = HashMap::new();
+    let thresholds = state.config.whale_thresholds;
+
+    for subscription in subscriptions.iter().filter(|subscription| subscription.last_snapshot < latest.id) {
+        let guild_id = GuildId::new(subscription.guild_id);
+        let settings = state.guild_settings.get(Some(guild_id));
+        let hour = (current_time() / 3600 % 24) as u8;
+        if settings.quiet_hours.or(state.config.quiet_hours).is_some_and(|quiet| quiet.contains(hour)) {
+            continue; // The moves are posted together once the quiet hours end
+        }
+
+        if let Entry::Vacant(entry) = baselines.entry(subscription.last_snapshot) {
+            match state.storage.snapshot(subscription.last_snapshot).await {
+                Ok(baseline) => entry.insert(baseline),
+                Err(e) => {
+                    println!("Failed to read snapshot {}: {}", subscription.last_snapshot, e);
+                    continue;
+                },
+            };
+        }
+
+        // Recorded first so a failed send or a restart never posts the same moves twice
+        if let Err(e) = state.storage.set_last_snapshot(subscription, latest.id).await {
+            println!("Failed to record the whale check of {}: {}", subscription.tick, e);
+            continue;
+        }
+        // Without an older snapshot there is nothing to compare yet
+        let Some(Some(baseline)) = baselines.get(&subscription.last_snapshot) else {
+            continue;
+        };
+
+        let moves = whale_moves(holders(baseline), holders(latest), &thresholds);
+        if moves.is_empty() {
+            continue;
+        }
+        let format = number_format(state, Some(guild_id), false);
+        let reply = settings.theme_reply(whales_reply(network, &format, latest, &moves));
+        let channel_id = ChannelId::new(subscription.channel_id);
+        if let Err(why) = channel_id.send_message(http, reply.into_message()).await {
+            println!("Failed to post the whale moves of {} to {}: {:?}", subscription.tick, channel_id, why);
+        }
+    }
+}
+
+fn holders(snapshot: &TokenSnapshot) -> &[Holder] {
+    snapshot.token.holder.as_deref().unwrap_or_default()
+}
+
+// The moves between two holder lists: a new top holder, new addresses in the top 10 and large balance changes.
+// Only addresses present in both lists are compared, one that dropped out of the list has an unknown balance.
+fn whale_moves(before: &[Holder], after: &[Holder], thresholds: &WhaleThresholds) -> Vec<WhaleMove> {
+    let (before, after) = (ranked(before), ranked(after));
+    if before.is_empty() || after.is_empty() {
+        return Vec::new(); // A snapshot taken while the holder list was missing
+    }
+    let previous: HashMap<&str, (usize, u128)> = before.iter().enumerate().map(|(rank, (address, amount))| (*address, (rank, *amount))).collect();
+    let mut moves = Vec::new();
+
+    if let (Some((old_top, _)), Some((new_top, amount))) = (before.first(), after.first()) {
+        if old_top != new_top {
+            moves.push(WhaleMove::NewTopHolder { address: new_top.to_string(), amount: *amount, previous: old_top.to_string() });
+        }
+    }
+
+    for (rank, (address, amount)) in after.iter().enumerate().take(NEW_ENTRY_RANK).skip(1) {
+        let was_in_top = matches!(previous.get(address), Some((old_rank, _)) if *old_rank < NEW_ENTRY_RANK);
+        if !was_in_top {
+            moves.push(WhaleMove::EnteredTop { address: address.to_string(), rank: rank + 1, amount: *amount });
+        }
+    }
+
+    for (rank, (address, amount)) in after.iter().enumerate().take(thresholds.top_n) {
+        let Some((_, before)) = previous.get(address) else {
+            continue;
+        };
+        let change = before.abs_diff(*amount);
+        if *before > 0 && change as f64 * 100.0 > *before as f64 * thresholds.change_pct {
+            moves.push(WhaleMove::BalanceChange { address: address.to_string(), rank: rank + 1, before: *before, after: *amount });
+        }
+    }
+    moves
+}
+
+// Addresses with their raw amounts, largest first. A malformed amount is left out rather than read as zero,
+// which would look like the holder sold everything.
+fn ranked(holders: &[Holder]) -> Vec<(&str, u128)> {
+    let mut ranked: Vec<(&str, u128)> = holders.iter()
+        .filter_map(|holder| Some((holder.address.as_str(), holder.amount.trim().parse().ok()?)))
+        .collect();
+    ranked.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+    ranked
+}
+
+fn whales_reply(network: &Network, format: &NumberFormat, latest: &TokenSnapshot, moves: &[WhaleMove]) -> Reply {
+    let token = &latest.token;
+    let decimals = TokenAmount::parse_decimals(&token.dec).unwrap_or(0);
+    let amount = |raw: u128| format.amount(&TokenAmount::new(raw, decimals), Field::Holding);
+
+    let mut lines: Vec<String> = moves.iter()
+        .map(|whale_move| match whale_move {
+            WhaleMove::NewTopHolder { address, amount: held, previous } => format!(
+                "👑 New top holder {} with {}, ahead of {}",
+                network.address_link(address), amount(*held), network.address_link(previous),
+            ),
+            WhaleMove::EnteredTop { address, rank, amount: held } => format!(
+                "🆕 {} entered the top {} at #{} with {}",
+                network.address_link(address), NEW_ENTRY_RANK, rank, amount(*held),
+            ),
+            WhaleMove::BalanceChange { address, rank, before, after } => {
+                let (icon, sign) = if after > before { ("📈", "+") } else { ("📉", "−") };
+                let percent = TokenAmount::new(before.abs_diff(*after), decimals).percent_of(&TokenAmount::new(*before, decimals));
+                format!(
+                    "{} #{} {} {} → {} ({}{})",
+                    icon, rank, network.address_link(address), amount(*before), amount(*after),
+                    sign, percent.map(|percent| format.percent(percent)).unwrap_or_else(|| "N/A".to_string()),
+                )
+            },
+        })
+        .collect();
+    if lines.len() > MAX_LINES {
+        let hidden = lines.len() - MAX_LINES;
+        lines.truncate(MAX_LINES);
+        lines.push(format!("…and {} more", hidden));
+    }
+
+    Reply::new().embed(theme::current().embed()
+        .title(format!("🐋 Whale moves in {}", token.tick.to_uppercase()))
+        .description(format!("Network: **{}**\n\n{}", network.label, lines.join("\n"))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: WhaleThresholds = WhaleThresholds { top_n: 5, change_pct: 10.0 };
+
+    fn holders(amounts: &[(&str, &str)]) -> Vec<Holder> {
+        amounts.iter().map(|(address, amount)| Holder { address: address.to_string(), amount: amount.to_string() }).collect()
+    }
+
+    // Eleven holders, so the last one is outside the announced top ten
+    fn top_list() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("a", "10000"), ("b", "9000"), ("c", "8000"), ("d", "7000"), ("e", "6000"), ("f", "5000"),
+            ("g", "4000"), ("h", "3000"), ("i", "2000"), ("j", "1000"), ("k", "500"),
+        ]
+    }
+
+    #[test]
+    fn unchanged_list_has_no_moves() {
+        let list = holders(&top_list());
+        assert!(whale_moves(&list, &list, &THRESHOLDS).is_empty());
+        assert!(whale_moves(&[], &list, &THRESHOLDS).is_empty());
+    }
+
+    #[test]
+    fn new_holder_entering_the_list() {
+        let before = holders(&top_list());
+        let mut after = top_list();
+        after.push(("new", "8500"));
+        let moves = whale_moves(&before, &holders(&after), &THRESHOLDS);
+        assert_eq!(moves, vec![WhaleMove::EnteredTop { address: "new".to_string(), rank: 3, amount: 8500 }]);
+    }
+
+    #[test]
+    fn holder_dropping_out_makes_room() {
+        // `b` is gone, `k` moves up into the top ten
+        let before = holders(&top_list());
+        let after: Vec<_> = top_list().into_iter().filter(|(address, _)| *address != "b").collect();
+        let moves = whale_moves(&before, &holders(&after), &THRESHOLDS);
+        assert_eq!(moves, vec![WhaleMove::EnteredTop { address: "k".to_string(), rank: 10, amount: 500 }]);
+    }
+
+    #[test]
+    fn new_top_holder() {
+        let before = holders(&top_list());
+        let mut after = top_list();
+        after[1].1 = "10500";
+        let moves = whale_moves(&before, &holders(&after), &THRESHOLDS);
+        assert_eq!(moves, vec![
+            WhaleMove::NewTopHolder { address: "b".to_string(), amount: 10500, previous: "a".to_string() },
+            WhaleMove::BalanceChange { address: "b".to_string(), rank: 1, before: 9000, after: 10500 },
+        ]);
+    }
+
+    #[test]
+    fn balance_changes_need_more_than_the_threshold() {
+        let before = holders(&top_list());
+        let mut at_threshold = top_list();
+        at_threshold[2].1 = "8800"; // Exactly 10%
+        assert!(whale_moves(&before, &holders(&at_threshold), &THRESHOLDS).is_empty());
+
+        let mut above = top_list();
+        above[2].1 = "7199"; // Just over 10% down
+        let moves = whale_moves(&before, &holders(&above), &THRESHOLDS);
+        assert_eq!(moves, vec![WhaleMove::BalanceChange { address: "c".to_string(), rank: 3, before: 8000, after: 7199 }]);
+
+        // Outside the watched top five a large change is not announced
+        let mut outside = top_list();
+        outside[6].1 = "3900";
+        assert!(whale_moves(&before, &holders(&outside), &THRESHOLDS).is_empty());
+    }
+
+    #[test]
+    fn malformed_amounts_are_left_out() {
+        let before = holders(&top_list());
+        let mut after = top_list();
+        after[3].1 = "n/a";
+        let moves = whale_moves(&before, &holders(&after), &THRESHOLDS);
+        // `d` is not reported as dumping its balance, `k` takes its place in the top ten
+        assert_eq!(moves, vec![WhaleMove::EnteredTop { address: "k".to_string(), rank: 10, amount: 500 }]);
+    }
+}
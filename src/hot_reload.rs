@@ -0,0 +1,126 @@
+// src/hot_reload.rs
+use crate::imports::*;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+// A JSON configuration file that can be swapped in while the bot is running
+pub trait ConfigFile: Sized + Send + Sync + 'static {
+    const PATH: &'static str;
+
+    // Parse and validate the file contents; an error keeps the previous configuration
+    fn parse(content: &str) -> Result<Self, ConfigError>;
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(io::Error),
+    Parse(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(e) => write!(f, "failed to read file: {}", e),
+            ConfigError::Parse(e) => write!(f, "failed to parse file: {}", e),
+            ConfigError::Invalid(reason) => write!(f, "invalid configuration: {}", reason),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(e: serde_json::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+// Holds the active version of a configuration file and reloads it when the file changes
+pub struct Reloadable<T> {
+    current: RwLock<Option<Arc<T>>>,
+    modified: Mutex<Option<SystemTime>>, // Modification time of the file that was last loaded
+}
+
+impl<T: ConfigFile> Reloadable<T> {
+    pub const fn new() -> Self {
+        Reloadable {
+            current: RwLock::new(None),
+            modified: Mutex::new(None),
+        }
+    }
+
+    // The configuration currently in use
+    pub fn current(&self) -> Arc<T> {
+        self.current.read().unwrap()
+            .clone()
+            .unwrap_or_else(|| panic!("{} is loaded in main before the bot starts", T::PATH))
+    }
+
+    // Read and validate the file, then swap it in; on error the old configuration stays active
+    fn load(&self) -> Result<(), ConfigError> {
+        let modified = file_modified(T::PATH);
+        let content = std::fs::read_to_string(T::PATH).map_err(ConfigError::Read)?;
+        let parsed = T::parse(&content)?;
+
+        *self.current.write().unwrap() = Some(Arc::new(parsed));
+        *self.modified.lock().unwrap() = modified;
+        Ok(())
+    }
+}
+
+impl<T: ConfigFile> Default for Reloadable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Object-safe view of a reloadable file so the watcher can hold several of them
+pub trait Watched: Send + Sync {
+    fn path(&self) -> &'static str;
+    fn reload(&self) -> Result<(), ConfigError>;
+    fn reload_if_changed(&self) -> Option<Result<(), ConfigError>>;
+}
+
+impl<T: ConfigFile> Watched for Reloadable<T> {
+    fn path(&self) -> &'static str {
+        T::PATH
+    }
+
+    fn reload(&self) -> Result<(), ConfigError> {
+        self.load()
+    }
+
+    fn reload_if_changed(&self) -> Option<Result<(), ConfigError>> {
+        let modified = file_modified(T::PATH);
+        let mut last_modified = self.modified.lock().unwrap();
+        if modified.is_none() || modified == *last_modified {
+            return None; // Unchanged, or missing while being replaced
+        }
+        // Remember the attempt so a broken file is reported once rather than on every poll
+        *last_modified = modified;
+        drop(last_modified);
+
+        Some(self.load())
+    }
+}
+
+fn file_modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// Poll the configuration files and swap in any that changed
+pub fn spawn_watcher(files: Vec<&'static dyn Watched>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            for file in &files {
+                match file.reload_if_changed() {
+                    Some(Ok(())) => println!("Reloaded {}", file.path()),
+                    Some(Err(e)) => println!("Keeping the previous {}: {}", file.path(), e),
+                    None => {},
+                }
+            }
+        }
+    });
+}
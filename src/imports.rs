@@ -14,8 +14,12 @@ pub use std::time::{SystemTime, UNIX_EPOCH}; // Time operations: current system
 
 // Serenity for Discord bot functionality
 pub use serenity::async_trait; // Provides support for async traits
-pub use serenity::builder::{CreateEmbed, CreateEmbedAuthor, CreateMessage}; // Create embeds, embedded authors, and messages (can be embeds)
+pub use serenity::builder::{CreateAttachment, CreateEmbed, CreateEmbedAuthor, CreateMessage}; // Create embeds, embedded authors, and messages (can be embeds)
+pub use serenity::builder::{CreateAutocompleteResponse, CreateCommand, CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage}; // Slash command registration and responses
+pub use serenity::builder::{CreateActionRow, CreateButton, EditInteractionResponse}; // Message buttons and the responses that update them
+pub use serenity::model::application::{ButtonStyle, CommandInteraction, CommandOptionType, ComponentInteraction, Interaction}; // Slash commands and the interactions they trigger
 pub use serenity::model::channel::Message; // Message sent in channel
+pub use serenity::model::id::ChannelId; // Channel identifiers used when sending replies
 pub use serenity::model::gateway::{GatewayIntents, Ready}; // Event for when the bot is ready, gateway intents
 pub use serenity::prelude::*; // Commonly used traits and types from Serenity
 
@@ -0,0 +1,299 @@
+// src/kasplex.rs
+use crate::imports::*;
+use crate::result_struct::{DataStruct, Holder, IndexerInfo, Operation, ResultStruct, TokenInfo};
+use crate::retry::{CircuitBreaker, RetryPolicy};
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15); // Give up on a request after this long
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const USER_AGENT: &str = concat!("nacho_bot/", env!("CARGO_PKG_VERSION"));
+
+// Response envelope shared by every Kasplex endpoint
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    prev: Option<String>,
+    #[serde(default)]
+    next: Option<String>,
+    result: Option<T>,
+}
+
+// Position in a paginated listing, taken from the `prev`/`next` fields of the previous page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    Next(String),
+    Prev(String),
+}
+
+// One page of a paginated listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub message: String,
+    pub prev: Option<String>,
+    pub next: Option<String>,
+    pub result: Vec<T>,
+}
+
+#[allow(dead_code)]
+impl<T> Page<T> {
+    pub fn next_cursor(&self) -> Option<Cursor> {
+        self.next.clone().filter(|next| !next.is_empty()).map(Cursor::Next)
+    }
+
+    pub fn prev_cursor(&self) -> Option<Cursor> {
+        self.prev.clone().filter(|prev| !prev.is_empty()).map(Cursor::Prev)
+    }
+}
+
+// Filters for /oplist; at least one of them should be set
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default)]
+pub struct OpListFilter {
+    pub tick: Option<String>,
+    pub address: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum KasplexError {
+    UnknownTicker(String),                          // The API has no data for this ticker
+    NotFound(String),                               // Any other missing resource, e.g. an unknown operation id
+    RateLimited { retry_after: Option<u64> },       // HTTP 429, optionally with the seconds to wait
+    ApiDown(String),                                // Connection problems, timeouts and 5xx responses
+    Api { status: u16, message: String },           // Any other rejection, with the Kasplex `message`
+    Malformed(String),                              // The response was not the JSON we expected
+    CircuitOpen,                                    // Too many recent failures, the API is not being called
+}
+
+impl KasplexError {
+    // Failures worth retrying and counting against the circuit breaker
+    pub fn is_transient(&self) -> bool {
+        matches!(self, KasplexError::ApiDown(_) | KasplexError::RateLimited { .. })
+    }
+
+    // Failures where showing older cached data beats showing nothing
+    pub fn is_unavailable(&self) -> bool {
+        self.is_transient() || matches!(self, KasplexError::CircuitOpen)
+    }
+}
+
+impl fmt::Display for KasplexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KasplexError::UnknownTicker(tick) => write!(f, "token {} not found", tick),
+            KasplexError::NotFound(what) => write!(f, "{} not found", what),
+            KasplexError::RateLimited { retry_after: Some(seconds) } => write!(f, "rate limited, retry after {}s", seconds),
+            KasplexError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            KasplexError::ApiDown(reason) => write!(f, "API unavailable: {}", reason),
+            KasplexError::Api { status, message } => write!(f, "API error {}: {}", status, message),
+            KasplexError::Malformed(reason) => write!(f, "unexpected response: {}", reason),
+            KasplexError::CircuitOpen => write!(f, "circuit breaker open, skipping the request"),
+        }
+    }
+}
+
+impl std::error::Error for KasplexError {}
+
+impl From<reqwest::Error> for KasplexError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            KasplexError::Malformed(e.to_string())
+        } else {
+            KasplexError::ApiDown(e.to_string()) // Connect errors, timeouts and broken bodies
+        }
+    }
+}
+
+// Map an HTTP status and the Kasplex `message` field onto an error variant
+fn classify(status: StatusCode, message: &str, retry_after: Option<u64>) -> KasplexError {
+    let lowered = message.to_lowercase();
+    if status == StatusCode::TOO_MANY_REQUESTS || lowered.contains("too many") || lowered.contains("rate limit") {
+        KasplexError::RateLimited { retry_after }
+    } else if status.is_server_error() {
+        KasplexError::ApiDown(format!("HTTP {} {}", status.as_u16(), message).trim().to_string())
+    } else if status == StatusCode::NOT_FOUND || lowered.contains("not found") {
+        KasplexError::NotFound(message.to_string())
+    } else {
+        KasplexError::Api { status: status.as_u16(), message: message.to_string() }
+    }
+}
+
+// Client for the Kasplex KRC20 indexer API, reusing one connection pool for every request
+#[derive(Debug, Clone)]
+pub struct KasplexClient {
+    http: reqwest::Client,
+    base_url: String,
+    retry: RetryPolicy,
+    breaker: Arc<CircuitBreaker>, // Shared by every clone so all callers see the same API health
+}
+
+#[allow(dead_code)] // Covers the whole API; not every endpoint has a command using it yet
+impl KasplexClient {
+    pub fn new(base_url: &str, retry: RetryPolicy, breaker: CircuitBreaker) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("Failed to build the HTTP client");
+
+        KasplexClient {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            retry,
+            breaker: Arc::new(breaker),
+        }
+    }
+
+    // Send a GET request, retrying transient failures with backoff unless the circuit breaker is open.
+    // Every endpoint is a read, so repeating a request is always safe.
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<Envelope<T>, KasplexError> {
+        if !self.breaker.allow() {
+            return Err(KasplexError::CircuitOpen);
+        }
+
+        let mut attempt = 0;
+        loop {
+            match self.get_once(path, query).await {
+                Err(e) if e.is_transient() && attempt < self.retry.max_retries => {
+                    // Honour the server's Retry-After when it fits within our backoff limit
+                    let delay = match &e {
+                        KasplexError::RateLimited { retry_after: Some(seconds) } => Duration::from_secs(*seconds).min(self.retry.max_delay),
+                        _ => self.retry.delay(attempt),
+                    };
+                    println!("Kasplex request {} failed ({}), retrying in {:?}", path, e, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                },
+                Err(e) if e.is_transient() => {
+                    self.breaker.record_failure();
+                    return Err(e);
+                },
+                result => {
+                    self.breaker.record_success(); // The API answered, even if the answer was an error
+                    return result;
+                },
+            }
+        }
+    }
+
+    // Send a single GET request and decode the response envelope
+    async fn get_once<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<Envelope<T>, KasplexError> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.http.get(url).query(query).send().await?;
+        let status = response.status();
+        let retry_after = response.headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            // Error responses usually still carry the envelope with a descriptive message
+            let message = serde_json::from_str::<Envelope<serde_json::Value>>(&body)
+                .map(|envelope| envelope.message)
+                .unwrap_or_default();
+            return Err(classify(status, &message, retry_after));
+        }
+
+        serde_json::from_str(&body).map_err(|e| KasplexError::Malformed(e.to_string()))
+    }
+
+    // Fetch one page of a paginated endpoint
+    async fn get_page<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)], cursor: Option<&Cursor>) -> Result<Page<T>, KasplexError> {
+        let mut query = query.to_vec();
+        match cursor {
+            Some(Cursor::Next(next)) => query.push(("next", next.as_str())),
+            Some(Cursor::Prev(prev)) => query.push(("prev", prev.as_str())),
+            None => {},
+        }
+
+        let envelope: Envelope<Vec<T>> = self.get(path, &query).await?;
+        Ok(Page {
+            message: envelope.message,
+            prev: envelope.prev,
+            next: envelope.next,
+            result: envelope.result.unwrap_or_default(),
+        })
+    }
+
+    // GET /info
+    pub async fn info(&self) -> Result<IndexerInfo, KasplexError> {
+        let envelope: Envelope<IndexerInfo> = self.get("/info", &[]).await?;
+        envelope.result.ok_or_else(|| KasplexError::NotFound("indexer info".to_string()))
+    }
+
+    // GET /token/tokenlist
+    pub async fn token_list(&self, cursor: Option<&Cursor>) -> Result<Page<ResultStruct>, KasplexError> {
+        self.get_page("/token/tokenlist", &[], cursor).await
+    }
+
+    // GET /token/{tick} with holder statistics
+    pub async fn token(&self, tick: &str) -> Result<DataStruct, KasplexError> {
+        self.token_query(tick, &[("stat", "true")]).await
+    }
+
+    // GET /token/{tick} with the top holders
+    pub async fn token_holders(&self, tick: &str) -> Result<Vec<Holder>, KasplexError> {
+        let data = self.token_query(tick, &[("holder", "true")]).await?;
+        Ok(data.result.into_iter().next().and_then(|result| result.holder).unwrap_or_default())
+    }
+
+    async fn token_query(&self, tick: &str, query: &[(&str, &str)]) -> Result<DataStruct, KasplexError> {
+        let path = format!("/token/{}", urlencoding::encode(tick));
+        let envelope: Envelope<Vec<ResultStruct>> = match self.get(&path, query).await {
+            Err(KasplexError::NotFound(_)) => return Err(KasplexError::UnknownTicker(tick.to_string())),
+            other => other?,
+        };
+
+        // An unknown ticker comes back as a null result
+        match envelope.result {
+            Some(result) if !result.is_empty() => Ok(DataStruct { message: envelope.message, result }),
+            _ => Err(KasplexError::UnknownTicker(tick.to_string())),
+        }
+    }
+
+    // GET /address/{addr}/tokenlist
+    pub async fn address_token_list(&self, address: &str, cursor: Option<&Cursor>) -> Result<Page<TokenInfo>, KasplexError> {
+        let path = format!("/address/{}/tokenlist", urlencoding::encode(address));
+        self.get_page(&path, &[], cursor).await
+    }
+
+    // GET /address/{addr}/token/{tick}
+    pub async fn address_token(&self, address: &str, tick: &str) -> Result<TokenInfo, KasplexError> {
+        let path = format!("/address/{}/token/{}", urlencoding::encode(address), urlencoding::encode(tick));
+        let envelope: Envelope<Vec<TokenInfo>> = self.get(&path, &[]).await?;
+
+        envelope.result
+            .and_then(|result| result.into_iter().next())
+            .ok_or_else(|| KasplexError::UnknownTicker(tick.to_string()))
+    }
+
+    // GET /oplist
+    pub async fn op_list(&self, filter: &OpListFilter, cursor: Option<&Cursor>) -> Result<Page<Operation>, KasplexError> {
+        let mut query = Vec::new();
+        if let Some(tick) = &filter.tick {
+            query.push(("tick", tick.as_str()));
+        }
+        if let Some(address) = &filter.address {
+            query.push(("address", address.as_str()));
+        }
+        self.get_page("/oplist", &query, cursor).await
+    }
+
+    // GET /op/{id}
+    pub async fn op(&self, id: &str) -> Result<Operation, KasplexError> {
+        let path = format!("/op/{}", urlencoding::encode(id));
+        let envelope: Envelope<Vec<Operation>> = self.get(&path, &[]).await?;
+
+        envelope.result
+            .and_then(|result| result.into_iter().next())
+            .ok_or_else(|| KasplexError::NotFound(format!("operation {}", id)))
+    }
+}
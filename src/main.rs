@@ -1,100 +1,174 @@
 #![allow(non_snake_case)]
-#[warn(non_camel_case_types)]
+#![warn(non_camel_case_types)]
 
 // Import necessary modules and make them publicly available
 mod imports;
 pub use imports::*;
 mod mint_status {
     pub mod datatweaks;
+    pub mod history;
+    pub mod milestones;
+    pub mod velocity;
 }    
 
 mod holder_status {
+    pub mod analytics;
     pub mod datatweaks;
+    pub mod watchlist;
+    pub mod whales;
 }  
 
 mod result_struct;
+mod address;
+mod amount;
+mod cache;
+mod charts;
+mod config;
+mod deployments;
+mod format;
+mod hot_reload;
+mod guild_settings;
+mod kasplex;
+mod networks;
+mod rate_limit;
+mod reply;
+mod retry;
+mod state;
+mod storage;
+mod theme;
 mod commands {
+    pub mod api_error;
+    pub mod chart;
     pub mod status;
+    pub mod topholders;
     pub mod links;    
     pub mod help;
     pub mod holder;
     pub mod donate;
+    pub mod network;
+    pub mod locale;
+    pub mod config;
+    pub mod rules;
+    pub mod subscribe;
+    pub mod deployments;
+    pub mod watch;
+    pub mod compare;
+    pub mod registry;
+    pub mod slash;
 }
 
-// Import the ResultStruct to use in main.rs
-use crate::result_struct::ResultStruct;
-
-// Define a struct for handling API data
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DataStruct {
-    message: String,
-    result: Vec<ResultStruct>
-}
+// Re-export the API data struct so the datatweaks modules can use crate::DataStruct
+pub use crate::result_struct::DataStruct;
+use crate::cache::MemoryCache;
+use crate::config::Config;
+use crate::commands::registry::Registry;
+use crate::guild_settings::GuildSettingsStore;
+use crate::hot_reload::Watched;
+use crate::networks::Networks;
+use crate::rate_limit::RateLimiter;
+use crate::state::BotState;
+use crate::storage::Storage;
+use std::sync::Arc;
 
 // Define a struct for handling events
 struct Handler {
-    api_base_url: String,
+    state: Arc<BotState>, // Config, API clients, guild settings and commands shared by every event
 }
 
 #[async_trait]
 impl EventHandler for Handler {
     async fn message(&self, ctx: Context, msg: Message) {
-        // Ignore messages from bots
-        if msg.author.bot {
+        // Ignore messages from bots, and all messages once prefix commands are switched off
+        if msg.author.bot || !self.state.config.prefix_commands {
             return;
         }
 
-        // Split the message into parts and extract the command
-        let message = msg.content.as_str();
-        let mut message_parts = message.split_whitespace();
-        let command = message_parts.next().unwrap();
-
-        // Call the status command handler
-        //if command == "!mint_status" {
-        //    commands::status::handle_status_command(&ctx, &msg, &mut message_parts, &self.api_base_url).await;
-        //}
-        
-        match command {
-            "!tokeninfo" => {
-                commands::status::handle_status_command(&ctx, &msg, &mut message_parts, &self.api_base_url).await;
+        self.state.registry.handle_message(&ctx, &msg, &self.state).await;
+    }
+
+    // Handle slash commands, ticker autocomplete and message buttons
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        match interaction {
+            Interaction::Command(command) => {
+                commands::slash::handle_slash_command(&ctx, &command, &self.state).await;
             },
-            "!links" => {
-                commands::links::handle_links_command(&ctx, &msg).await; // Add this line
+            Interaction::Autocomplete(command) => {
+                commands::slash::handle_autocomplete(&ctx, &command, &self.state).await;
             },
-            "!helpme" => {
-                commands::help::handle_help_command(&ctx, &msg).await;
+            Interaction::Component(component) => {
+                self.state.registry.handle_component(&ctx, &component, &self.state).await;
             },
-            "!tokenbalance" => {
-                commands::holder::handle_holder_command(&ctx, &msg, &mut message_parts, &self.api_base_url).await;
-            }, 
-            "!donate" => {
-                commands::donate::handle_donate_command(&ctx, &msg).await;
-            },                         
             _ => {},
         }
-
     }
 
     // Handle the "ready" event when the bot is connected
-    async fn ready(&self, _: Context, ready: Ready) {
+    async fn ready(&self, ctx: Context, ready: Ready) {
         println!("{} is connected!", ready.user.name);
+        commands::slash::register_commands(&ctx, &self.state).await;
     }
 }
 
 #[tokio::main]
 async fn main() {
     let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
-    // Read the API base URL from the environment variable or default to testnet
-    let api_base_url = env::var("KASPLEX_API_BASE_URL").unwrap_or_else(|_| "https://tn11api.kasplex.org/v1/krc20".to_string());
+    let config = Config::from_env();
 
-    let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    // Load the JSON configuration, refusing to start with a broken file
+    let config_files: Vec<&'static dyn Watched> = vec![&theme::THEME, &commands::links::LINKS];
+    for file in &config_files {
+        if let Err(e) = file.reload() {
+            panic!("Failed to load {}: {}", file.path(), e);
+        }
+    }
+    // Pick up edits to those files without restarting the container
+    hot_reload::spawn_watcher(config_files, config.reload_interval);
 
+    // Slash commands need no privileged intents, message content is only required for prefix commands
+    let mut intents = GatewayIntents::GUILDS;
+    if config.prefix_commands {
+        intents |= GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT;
+    }
+
+    // One API client per network in networks.json
+    let networks = Networks::load(&config).unwrap_or_else(|e| panic!("Failed to load {}: {}", networks::NETWORKS_FILE, e));
+    let guild_settings = GuildSettingsStore::load().expect("Failed to load the guild settings");
+
+    let storage = Storage::open(&config.database_path)
+        .unwrap_or_else(|e| panic!("Failed to open {}: {}", config.database_path, e));
+
+    // API responses are cached in memory, optionally starting from the snapshot in the database
+    let cache = Arc::new(MemoryCache::new(config.cache_ttls.clone()));
+    let snapshot = config.cache_snapshot.then(|| storage.clone());
+    if let Some(storage) = &snapshot {
+        match storage.load_cache_entries().await {
+            Ok(entries) => {
+                println!("Loaded {} cache entries from the database", entries.len());
+                cache.extend(entries);
+            },
+            Err(e) => println!("Starting with an empty cache: {}", e),
+        }
+    }
+    cache::spawn_maintenance(cache.clone(), config.cache_snapshot_interval, config.cache_max_age, snapshot);
+
+    let rate_limiter = RateLimiter::new(config.rate_limits.clone());
+    let state = Arc::new(BotState { config, networks, guild_settings, cache, storage, registry: Registry::new(), rate_limiter });
+    mint_status::history::spawn_recorder(state.clone(), state.config.history_interval);
+    mint_status::history::spawn_pruner(state.clone(), state.config.history_max_age);
+
+    let alert_state = state.clone();
     let discord_task = tokio::spawn(async move {
         let mut client = Client::builder(&token, intents)
-            .event_handler(Handler { api_base_url }) // Pass the api_base_url to the handler
+            .event_handler(Handler { state }) // Pass the shared state to the handler
             .await
             .expect("Err creating client");
 
+        // Alerts are posted outside of any event, through the client's HTTP handle
+        mint_status::milestones::spawn_scheduler(alert_state.clone(), client.http.clone());
+        deployments::spawn_announcer(alert_state.clone(), client.http.clone());
+        holder_status::watchlist::spawn_watcher(alert_state.clone(), client.http.clone());
+        holder_status::whales::spawn_scheduler(alert_state, client.http.clone());
+
         // Start the client
         if let Err(why) = client.start().await {
             println!("Client error: {:?}", why);
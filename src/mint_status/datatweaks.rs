@@ -1,132 +1,77 @@
 use crate::DataStruct; // Import the DataStruct from the current crate
 use crate::imports::*; // Import everything from the imports module
-use serde_json::Value;
-use std::fs;
-use rand::distributions::{Distribution, WeightedIndex};
-use rand::thread_rng;
-use serenity::builder::CreateEmbedFooter;
+use crate::amount::{AmountError, TokenAmount, TokenAmounts};
+use crate::format::{Field, NumberFormat};
+use crate::cache::{self, Cache, CacheKey, Cached, Endpoint};
+use crate::kasplex::KasplexError;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::holder_status::analytics::{self, ConcentrationThresholds};
+use crate::mint_status::velocity::{self, MintVelocity};
+use crate::storage::Storage;
+use crate::theme;
 use chrono::{ Utc, TimeZone};
 
-
-const STORAGE_FOLDER: &str = "data_storage"; // Define a constant for the storage folder name
-const PERIOD_LIMIT: u64 = 600; // Define time retention in files for cache
-
-// Define a struct for metadata to store the timestamp
-#[derive(Serialize, Deserialize)]
-struct Metadata {
-    timestamp: u64,
-}
-
-// Get the current time in seconds since the UNIX epoch
-pub fn current_time() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards") // Handle potential error where system time is before the UNIX epoch
-        .as_secs()
-}
-
-// Ensure the storage folder exists, creating it if necessary
-fn ensure_storage_folder_exists() -> io::Result<()> {
-    create_dir_all(STORAGE_FOLDER)
-}
-
-fn format_large_number(number: f64) -> String {
-    const BILLION: f64 = 1_000_000_000.0;
-    const MILLION: f64 = 1_000_000.0;
-    const THOUSAND: f64 = 1_000.0;
-
-    if number >= BILLION {
-        format!("{:.2}B", number / BILLION)
-    } else if number >= MILLION {
-        format!("{:.2}M", number / MILLION)
-    } else if number >= THOUSAND {
-        format!("{:.2}K", number / THOUSAND)
-    } else {
-        format!("{}", number)
+// Fetch the token info and its holder list through the cache, each with its own time to live.
+// Every fresh fetch is recorded in the history; when the API is down and nothing is cached the last recorded snapshot is used.
+pub async fn fetch_token(cache: &dyn Cache, storage: &Storage, network: &Network, token: &str) -> Result<Cached<DataStruct>, KasplexError> {
+    match fetch_and_record(cache, storage, network, token).await {
+        Err(e) if e.is_unavailable() => match storage.latest_snapshot(&network.name, token).await {
+            Ok(Some(snapshot)) => {
+                println!("Serving the last recorded snapshot of {} on {}: {}", token, network.name, e);
+                Ok(Cached {
+                    value: DataStruct { message: "snapshot".to_string(), result: vec![snapshot.token] },
+                    fetched_at: snapshot.taken_at,
+                    stale: true,
+                    fetched: false,
+                })
+            },
+            _ => Err(e),
+        },
+        other => other,
     }
 }
 
-
-// Check if five minutes have passed since the last fetch for the given token
-pub fn check_time(token: &str) -> io::Result<(bool, bool)> {
-    let current_time = current_time(); // Get the current time
-    ensure_storage_folder_exists()?; // Ensure the storage folder exists
-    let json_name_time = format!("{}/{}_metadata.json", STORAGE_FOLDER, token); // Construct the metadata file path
-
-    // Try to open the metadata file
-    let mut file = match OpenOptions::new().read(true).open(&json_name_time) {
-        Ok(file) => file,
-        Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
-            return Ok((true, true)); // Return true if it's the first time checking
-        }
-        Err(e) => return Err(e), // Return the error if there was an issue opening the file
-    };
-
-    let mut json = String::new();
-    file.read_to_string(&mut json)?; // Read the file contents into a string
-    
-    let metadata: Metadata = serde_json::from_str(&json)?; // Deserialize the JSON string into Metadata
-
-    let time_period_passed = current_time > metadata.timestamp + PERIOD_LIMIT; // Check if five minutes have passed since the last timestamp
-    Ok((time_period_passed, false))
+// Fetch a token for a user's command. Besides fetch_token this marks the token as looked at,
+// which keeps the history recorder sampling it for a while.
+pub async fn view_token(cache: &dyn Cache, storage: &Storage, network: &Network, token: &str) -> Result<Cached<DataStruct>, KasplexError> {
+    let data = fetch_token(cache, storage, network, token).await?;
+    if let Err(e) = storage.record_view(&network.name, token, cache::current_time()).await {
+        println!("Failed to record the view of {}: {}", token, e);
+    }
+    Ok(data)
 }
 
-// Save data and its associated metadata (timestamp) to JSON files
-pub fn save_data(data: &DataStruct, token: &str) -> io::Result<()> {
-    ensure_storage_folder_exists()?; // Ensure the storage folder exists
-
-    let json_data = serde_json::to_string(data)?; // Serialize the data to a JSON string
-    let json_name = format!("{}/{}_data.json", STORAGE_FOLDER, token); // Construct the data file path
-    let mut file = File::create(&json_name)?; // Create the data file
-    file.write_all(json_data.as_bytes())?; // Write the JSON data to the file
-
-    let timestamp = current_time(); // Get the current timestamp
-    let metadata = Metadata { timestamp }; // Create a Metadata instance with the current timestamp
+async fn fetch_and_record(cache: &dyn Cache, storage: &Storage, network: &Network, token: &str) -> Result<Cached<DataStruct>, KasplexError> {
+    let info_key = CacheKey::new(&network.name, Endpoint::TokenInfo, token);
+    let holders_key = CacheKey::new(&network.name, Endpoint::HolderList, token);
 
-    let json_name_time = format!("{}/{}_metadata.json", STORAGE_FOLDER, token); // Construct the metadata file path
-    let json_time = serde_json::to_string(&metadata)?; // Serialize the metadata to a JSON string
-    let mut file_time = File::create(&json_name_time)?; // Create the metadata file
-    file_time.write_all(json_time.as_bytes())?; // Write the JSON metadata to the file
-    Ok(())
-}
-
-// Fetch data from a JSON file for the given token
-pub fn fetch_from_json(token: &str) -> io::Result<DataStruct> {
-    ensure_storage_folder_exists()?; // Ensure the storage folder exists
-
-    let json_name = format!("{}/{}_data.json", STORAGE_FOLDER, token); // Construct the data file path
-    let path = Path::new(&json_name);
-    let mut file = File::open(&path)?; // Open the data file
-    let mut json_data = String::new();
-    file.read_to_string(&mut json_data)?; // Read the file contents into a string
-    
-    let data: DataStruct = serde_json::from_str(&json_data).expect("Failed to deserialize data"); // Deserialize the JSON string into DataStruct
-    Ok(data)
-}
+    let mut info: Cached<DataStruct> = cache::get_or_fetch(cache, info_key, || network.client.token(token)).await?;
+    let holders = cache::get_or_fetch(cache, holders_key, || network.client.token_holders(token)).await?;
 
-// Fetch data from the API for the given token
-pub async fn fetch_from_api(api_base_url: &str, token: &str) -> Result<DataStruct, Error> {
-    let url = format!("{}/token/{}?stat=true&holder=true", api_base_url, token); // Construct the API URL using the base URL
-    let response = reqwest::get(url).await?.json::<DataStruct>().await?; // Send a GET request and parse the JSON response
-    Ok(response)
-}
+    info.value.result[0].holder = Some(holders.value);
+    info.stale |= holders.stale;
+    info.fetched |= holders.fetched;
+    info.fetched_at = info.fetched_at.min(holders.fetched_at); // Report the age of the older half
 
-// Helper function to format the optional values
-fn format_option(value: &Option<String>) -> String {
-    value.as_deref().unwrap_or("N/A").to_string()
+    if info.fetched && !info.stale {
+        // A holder list that came from the cache was already recorded with the snapshot that fetched it
+        let mut snapshot = info.value.result[0].clone();
+        if !holders.fetched {
+            snapshot.holder = None;
+        }
+        if let Err(e) = storage.record_snapshot(&network.name, &snapshot, cache::current_time()).await {
+            println!("Failed to record the {} snapshot: {}", token, e);
+        }
+    }
+    Ok(info)
 }
 
-// Helper function to select a random background image based on weight
-fn select_random_banner(banners: &Vec<Value>) -> &str {
-    let weights: Vec<_> = banners.iter().map(|b| b["weight"].as_u64().unwrap_or(1) as u32).collect();
-    let dist = WeightedIndex::new(&weights).unwrap();
-    let mut rng = thread_rng();
-    let index = dist.sample(&mut rng);
-    banners[index]["url"].as_str().unwrap()
+// Helper function to format the optional counts
+fn format_option(format: &NumberFormat, value: &Option<String>) -> String {
+    value.as_deref().map(|count| format.count_text(count)).unwrap_or_else(|| "N/A".to_string())
 }
 
-
-
 fn format_timestamp(timestamp: u64) -> String {
     // Convert timestamp to UTC datetime
     let datetime_utc = Utc.timestamp_opt(timestamp as i64, 0).single().unwrap();
@@ -152,42 +97,28 @@ fn format_timestamp(timestamp: u64) -> String {
     format!("Last status is from {}", formatted_duration)
 }
 
+pub fn format_data(network: &Network, format: &NumberFormat, data: Cached<DataStruct>, velocity: Option<MintVelocity>, distribution: Option<&ConcentrationThresholds>, chart: Option<CreateAttachment>) -> Reply {
+    let theme = theme::current();
 
+    let result = &data.value.result[0];
 
-
-pub async fn format_data(data: DataStruct) -> CreateMessage {
-    // Load the JSON template
-    let template_content = fs::read_to_string("message_template.json").expect("Failed to read message template");
-    let template: Value = serde_json::from_str(&template_content).expect("Failed to parse message template");
-
-    // Ensure required fields are present in the template
-    let color = 0xADD8E6; // Light blue color
-    let background_images = template["background_images"].as_array().expect("Background images not found in message template");
-    let background_image_url = select_random_banner(background_images);
-    let author_name = template["author"]["name"].as_str().expect("Author name not found in message template");
-    let author_icon_url = template["author"]["icon_url"].as_str().expect("Author icon URL not found in message template");
-
-    let result = &data.result[0];
-
-    // Get the current timestamp and format it
-    let metadata_content = fs::read_to_string(format!("{}/{}_metadata.json", STORAGE_FOLDER, result.tick)).expect("Failed to read metadata file");
-    let metadata: Metadata = serde_json::from_str(&metadata_content).expect("Failed to parse metadata");
-    let formatted_timestamp = format_timestamp(metadata.timestamp);
+    // Format how long ago the data was fetched
+    let mut formatted_timestamp = format_timestamp(data.fetched_at);
+    if data.stale {
+        formatted_timestamp = format!("⚠️ The Kasplex API is unavailable, data may be stale.\n{}", formatted_timestamp);
+    }
+    formatted_timestamp = format!("Network: **{}**\n{}", network.label, formatted_timestamp);
 
     // Create the message payload with an embedded message
-    let mut embed = CreateEmbed::new()
-        .color(color)
-        .image(background_image_url)
-        .author(CreateEmbedAuthor::new(author_name).icon_url(author_icon_url))
-        .description(formatted_timestamp) // Add the timestamp description
-        .footer(CreateEmbedFooter::new("x.com/coinchimpx"));
+    let mut embed = theme.status_embed()
+        .description(formatted_timestamp); // Add the timestamp description
 
     // Add content and footer
     let content = format!("**# Mint Status for {}**", result.tick.to_uppercase());
 
     // Check the state of the token
     if result.state == "unused" {
-        let payload = CreateMessage::new()
+        let payload = Reply::new()
             .content(content)
             .embed(embed.field(
                 "Token Status",
@@ -200,42 +131,39 @@ pub async fn format_data(data: DataStruct) -> CreateMessage {
     // Format the token name for display
     let formatted_token = format!("% {} Minted", result.tick.to_uppercase());
 
-    let progress = result.minted.parse::<f64>().expect("Not a valid f64") / result.max.parse::<f64>().expect("Not a valid f64");
-    let formatted_progress = format!("{:.2}%", progress * 100.0);
+    // Every amount is exact, a field the API sent malformed shows as N/A instead of failing the reply
+    let amounts = TokenAmounts::new(&result.tick, &result.dec);
+    let amount = |raw: &str| amounts.parse(raw);
+    let (max, lim, minted) = (amount(&result.max), amount(&result.lim), amount(&result.minted));
+
+    let formatted_progress = match (&minted, &max) {
+        (Ok(minted), Ok(max)) => minted.percent_of(max).map(|percent| format.percent(percent)),
+        _ => None,
+    }.unwrap_or_else(|| "N/A".to_string());
 
     // Format large numbers
-    let dec = result.dec.parse::<f64>().expect("Not a valid f64");
-    let max = result.max.parse::<f64>().expect("Not a valid f64") / 10f64.powf(dec);
-    let lim = result.lim.parse::<f64>().expect("Not a valid f64") / 10f64.powf(dec);
-    let minted = result.minted.parse::<f64>().expect("Not a valid f64") / 10f64.powf(dec);
-    let formatted_max = format_large_number(max);
-    let formatted_lim = format_large_number(lim);
-    let formatted_minted = format_large_number(minted);
+    let formatted_max = format.amount_or_na(&max, Field::Supply);
+    let formatted_lim = format.amount_or_na(&lim, Field::Supply);
+    let formatted_minted = format.amount_or_na(&minted, Field::Supply);
 
     // Calculate the sums for the top holders
     let empty_vec = Vec::new();
     let holders = result.holder.as_ref().unwrap_or(&empty_vec);
-    let sum_top_50: f64 = holders.iter().take(50).map(|h| h.amount.parse::<f64>().unwrap_or(0.0)).sum();
-    let sum_top_10: f64 = holders.iter().take(10).map(|h| h.amount.parse::<f64>().unwrap_or(0.0)).sum();
-    let sum_top_1: f64 = holders.iter().take(1).map(|h| h.amount.parse::<f64>().unwrap_or(0.0)).sum();
+    let holder_amounts: Vec<TokenAmount> = holders.iter().filter_map(|h| amount(&h.amount).ok()).collect();
+    let top_sum = |count: usize| amounts.decimals().map(|dec| TokenAmount::sum(dec, holder_amounts.iter().take(count)));
 
-    let formatted_sum_top_50 = format_large_number(sum_top_50 / 10f64.powf(dec));
-    let formatted_sum_top_10 = format_large_number(sum_top_10 / 10f64.powf(dec));
-    let formatted_sum_top_1 = format_large_number(sum_top_1 / 10f64.powf(dec));
+    let formatted_sum_top_50 = format.amount_or_na(&top_sum(50), Field::Holding);
+    let formatted_sum_top_10 = format.amount_or_na(&top_sum(10), Field::Holding);
+    let formatted_sum_top_1 = format.amount_or_na(&top_sum(1), Field::Holding);
 
-    let pre_allocation_amount = result.pre.parse::<f64>().unwrap_or(0.0) / 10f64.powf(dec);
-    let pre_allocation_desc = if pre_allocation_amount == 0.0 {
-        "Fair Launch".to_string()
-    } else {
-        format_large_number(pre_allocation_amount)
-    };
+    let pre_allocation_desc = format_pre_allocation(format, &amounts.parse_pre(&result.pre));
 
     // Create the message payload with an embedded message
     embed = embed
         .field(formatted_token, formatted_progress, true)
         .field("Pre-Allocation", pre_allocation_desc, true) // Add this line
-        .field("Mints", format_option(&result.mintTotal), true)
-        .field("Holders", format_option(&result.holderTotal), true)
+        .field("Mints", format_option(format, &result.mintTotal), true)
+        .field("Holders", format_option(format, &result.holderTotal), true)
         .field("Max Supply", formatted_max, true)
         .field("Limit", formatted_lim, true)
         .field("Minted", formatted_minted, true)
@@ -243,7 +171,49 @@ pub async fn format_data(data: DataStruct) -> CreateMessage {
         .field("Top 10 Holders", formatted_sum_top_10, true)
         .field("Top Holder", formatted_sum_top_1, true);
 
-    CreateMessage::new()
+    if let Some(velocity) = velocity.filter(|velocity| !velocity.finished) {
+        embed = add_velocity_fields(embed, format, &velocity);
+    }
+
+    // Concentration of the supply among the listed top holders
+    let minted_raw = minted.as_ref().map(TokenAmount::raw).unwrap_or(0);
+    if let (Some(thresholds), Some(analytics)) = (distribution, analytics::analyze(holders, minted_raw)) {
+        embed = embed.field("Distribution", analytics::distribution_lines(&analytics, thresholds, format).join("\n"), false);
+    }
+
+    let mut reply = Reply::new()
         .content(content)
-        .embed(embed)
-}
\ No newline at end of file
+        .embed(embed);
+    if let Some(chart) = chart {
+        reply = reply.attachment(chart);
+    }
+    reply
+}
+
+// The pre-allocated amount, or Fair Launch when nothing was pre-allocated (see `TokenAmounts::parse_pre`)
+pub fn format_pre_allocation(format: &NumberFormat, pre: &Result<TokenAmount, AmountError>) -> String {
+    match pre {
+        Ok(pre) if pre.is_zero() => "Fair Launch".to_string(),
+        pre => format.amount_or_na(pre, Field::Supply),
+    }
+}
+
+// Mints per hour for each window and the time left until the max supply is reached
+fn add_velocity_fields(embed: CreateEmbed, format: &NumberFormat, velocity: &MintVelocity) -> CreateEmbed {
+    let rates: Vec<String> = velocity.rates.iter()
+        .map(|(label, rate)| match rate {
+            Some(rate) => format!("{}: {}/h", label, format.decimal(*rate, 1)),
+            None => format!("{}: N/A", label),
+        })
+        .collect();
+
+    let outlook = match (velocity.stalled_for, velocity.eta_secs) {
+        (Some(idle), _) => format!("⚠️ Stalled, no new mints for {}", velocity::format_duration(idle)),
+        (None, Some(eta)) => format!("~{}", velocity::format_duration(eta)),
+        (None, None) => "Not enough history yet".to_string(),
+    };
+
+    embed
+        .field("Mint Rate", rates.join("\n"), true)
+        .field("Fully Minted In", outlook, true)
+}
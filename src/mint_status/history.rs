@@ -0,0 +1,55 @@
+// src/mint_status/history.rs
+use crate::cache::current_time;
+use crate::mint_status::datatweaks;
+use crate::state::BotState;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TRACK_FOR: u64 = 7 * 24 * 3600; // Keep sampling a token for a week after a user last looked at it
+const FULL_DETAIL_FOR: u64 = 24 * 3600; // Every snapshot of the last day is kept, older ones are thinned out to one per hour
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+// Keep sampling the tokens that are still minting so the velocity has a continuous history,
+// not only the moments someone ran !tokeninfo
+pub fn spawn_recorder(state: Arc<BotState>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let tokens = match state.storage.minting_tokens(current_time().saturating_sub(TRACK_FOR)).await {
+                Ok(tokens) => tokens,
+                Err(e) => {
+                    println!("Failed to list the tokens to sample: {}", e);
+                    continue;
+                },
+            };
+
+            for (network_name, tick) in tokens {
+                let network = match state.networks.get(&network_name) {
+                    Some(network) => network,
+                    None => continue, // The network was removed from networks.json
+                };
+                // Goes through the cache, so a token someone just looked at is not fetched twice
+                if let Err(e) = datatweaks::fetch_token(state.cache.as_ref(), &state.storage, network, &tick).await {
+                    println!("Failed to sample {} on {}: {}", tick, network_name, e);
+                }
+            }
+        }
+    });
+}
+
+// Keep the database from growing without bound: thin out the older snapshots and delete those past the max age
+pub fn spawn_pruner(state: Arc<BotState>, max_age: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let now = current_time();
+            match state.storage.prune_snapshots(now.saturating_sub(FULL_DETAIL_FOR), now.saturating_sub(max_age.as_secs())).await {
+                Ok(0) => {},
+                Ok(removed) => println!("Pruned {} old snapshots", removed),
+                Err(e) => println!("Failed to prune the snapshots: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,179 @@
+// src/mint_status/milestones.rs
+use crate::imports::*;
+use crate::amount::{TokenAmount, TokenAmounts};
+use crate::cache::current_time;
+use crate::commands::registry::number_format;
+use crate::format::{Field, NumberFormat};
+use crate::mint_status::datatweaks;
+use crate::networks::Network;
+use crate::reply::Reply;
+use crate::result_struct::ResultStruct;
+use crate::state::BotState;
+use crate::storage::{Subscription, SubscriptionKind};
+use crate::theme;
+use serenity::http::Http;
+use serenity::model::id::GuildId;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+pub const DEFAULT_THRESHOLDS: &[u32] = &[25, 50, 75, 90, 99, 100];
+
+// Hours of the day (UTC) in which alerts wait; `start` is included, `end` is not and may be past midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: u8,
+    pub end: u8,
+}
+
+impl QuietHours {
+    // `22-7` for 22:00 to 07:00, `off` for never quiet
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.trim().eq_ignore_ascii_case("off") {
+            return Some(QuietHours { start: 0, end: 0 });
+        }
+        let (start, end) = value.trim().split_once('-')?;
+        let hour = |value: &str| value.trim().trim_end_matches(":00").parse::<u8>().ok().filter(|hour| *hour < 24);
+        Some(QuietHours { start: hour(start)?, end: hour(end)? })
+    }
+
+    pub fn contains(&self, hour: u8) -> bool {
+        match self.start <= self.end {
+            true => self.start <= hour && hour < self.end,
+            false => hour >= self.start || hour < self.end,
+        }
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.start == self.end {
+            true => write!(f, "off"),
+            false => write!(f, "{:02}:00–{:02}:00 UTC", self.start, self.end),
+        }
+    }
+}
+
+// Comma separated percentages such as `25,50,75,100`, sorted; invalid entries are dropped
+pub fn parse_thresholds(value: &str) -> Vec<u32> {
+    let mut thresholds: Vec<u32> = value.split(',')
+        .filter_map(|percent| percent.trim().trim_end_matches('%').parse().ok())
+        .filter(|percent| (1..=100).contains(percent))
+        .collect();
+    thresholds.sort_unstable();
+    thresholds.dedup();
+    thresholds
+}
+
+// Highest threshold the token's mint progress has reached, 0 for none.
+// Compared on the exact amounts so 99.996% does not count as 100%.
+pub fn reached_milestone(token: &ResultStruct, thresholds: &[u32]) -> Option<u32> {
+    let decimals = TokenAmount::parse_decimals(&token.dec).ok()?;
+    let max = TokenAmount::parse(&token.max, decimals).ok()?.raw();
+    let minted = TokenAmount::parse(&token.minted, decimals).ok()?.raw();
+    if token.state == "unused" || max == 0 {
+        return None;
+    }
+
+    let reached = thresholds.iter()
+        .filter(|percent| minted.saturating_mul(100) >= max.saturating_mul(**percent as u128))
+        .max();
+    Some(reached.copied().unwrap_or(0))
+}
+
+// Post milestone alerts to the subscribed channels, checking every interval through the cached status data
+pub fn spawn_scheduler(state: Arc<BotState>, http: Arc<Http>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(state.config.milestone_interval);
+        loop {
+            ticker.tick().await;
+            let subscriptions = match state.storage.subscriptions(None, Some(SubscriptionKind::Milestones)).await {
+                Ok(subscriptions) => subscriptions,
+                Err(e) => {
+                    println!("Failed to list the milestone subscriptions: {}", e);
+                    continue;
+                },
+            };
+
+            // Fetch each token once however many channels follow it, skipping those with nothing left to announce
+            let last_threshold = state.config.milestone_thresholds.last().copied().unwrap_or(100);
+            let mut tokens: BTreeMap<(String, String), Vec<Subscription>> = BTreeMap::new();
+            for subscription in subscriptions.into_iter().filter(|subscription| subscription.last_milestone < last_threshold) {
+                tokens.entry((subscription.network.clone(), subscription.tick.clone())).or_default().push(subscription);
+            }
+
+            for ((network_name, tick), subscriptions) in tokens {
+                let network = match state.networks.get(&network_name) {
+                    Some(network) => network,
+                    None => continue, // The network was removed from networks.json
+                };
+                let data = match datatweaks::fetch_token(state.cache.as_ref(), &state.storage, network, &tick).await {
+                    Ok(data) if !data.stale => data,
+                    Ok(_) => continue, // Wait for fresh data rather than announce from an old snapshot
+                    Err(e) => {
+                        println!("Failed to check the milestones of {} on {}: {}", tick, network_name, e);
+                        continue;
+                    },
+                };
+                let token = &data.value.result[0];
+                let reached = match reached_milestone(token, &state.config.milestone_thresholds) {
+                    Some(reached) => reached,
+                    None => continue,
+                };
+
+                for subscription in subscriptions.iter().filter(|subscription| reached > subscription.last_milestone) {
+                    announce(&state, &http, network, token, subscription, reached).await;
+                }
+            }
+        }
+    });
+}
+
+// Send one alert, unless it is quiet hours for the guild; the alert then goes out once they end
+async fn announce(state: &BotState, http: &Http, network: &Network, token: &ResultStruct, subscription: &Subscription, milestone: u32) {
+    let guild_id = GuildId::new(subscription.guild_id);
+    let settings = state.guild_settings.get(Some(guild_id));
+    let quiet_hours = settings.quiet_hours.or(state.config.quiet_hours);
+    let hour = (current_time() / 3600 % 24) as u8;
+    if quiet_hours.is_some_and(|quiet| quiet.contains(hour)) {
+        return;
+    }
+
+    // Recorded before sending so a failed send or a restart never posts the same milestone twice
+    if let Err(e) = state.storage.set_last_milestone(subscription, milestone).await {
+        println!("Failed to record the {}% milestone of {}: {}", milestone, subscription.tick, e);
+        return;
+    }
+
+    let format = number_format(state, Some(guild_id), false);
+    let reply = settings.theme_reply(milestone_reply(network, &format, token, milestone));
+    let channel_id = ChannelId::new(subscription.channel_id);
+    if let Err(why) = channel_id.send_message(http, reply.into_message()).await {
+        println!("Failed to post the {}% milestone of {} to {}: {:?}", milestone, subscription.tick, channel_id, why);
+    }
+}
+
+fn milestone_reply(network: &Network, format: &NumberFormat, token: &ResultStruct, milestone: u32) -> Reply {
+    let tick = token.tick.to_uppercase();
+    let headline = match milestone {
+        100 => format!("🎉 {} is fully minted!", tick),
+        _ => format!("🚀 {} has passed {}% minted", tick, milestone),
+    };
+
+    let amounts = TokenAmounts::new(&token.tick, &token.dec);
+    let (minted, max) = (amounts.parse(&token.minted), amounts.parse(&token.max));
+    let progress = match (&minted, &max) {
+        (Ok(minted), Ok(max)) => minted.percent_of(max).map(|percent| format.percent(percent)),
+        _ => None,
+    }.unwrap_or_else(|| "N/A".to_string());
+    let count = |value: &Option<String>| value.as_deref().map(|count| format.count_text(count)).unwrap_or_else(|| "N/A".to_string());
+
+    Reply::new().embed(theme::current().embed()
+        .title(headline)
+        .description(format!("Network: **{}**", network.label))
+        .field("Progress", progress, true)
+        .field("Minted", format.amount_or_na(&minted, Field::Supply), true)
+        .field("Max Supply", format.amount_or_na(&max, Field::Supply), true)
+        .field("Mints", count(&token.mintTotal), true)
+        .field("Holders", count(&token.holderTotal), true))
+}
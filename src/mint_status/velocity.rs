@@ -0,0 +1,192 @@
+// src/mint_status/velocity.rs
+use crate::amount::TokenAmount;
+use crate::result_struct::ResultStruct;
+use crate::storage::HistorySample;
+
+const HOUR: u64 = 3600;
+pub const WINDOWS: [(&str, u64); 3] = [("1h", HOUR), ("24h", 24 * HOUR), ("7d", 7 * 24 * HOUR)]; // Label and length of each velocity window
+const MIN_COVERAGE: f64 = 0.5; // A window needs samples spanning at least half its length to be shown
+const STALL_AFTER: u64 = 6 * HOUR; // No new mints for this long while supply is left counts as stalled
+
+// Mint speed of a token, measured from the recorded snapshots
+#[derive(Debug, Clone)]
+pub struct MintVelocity {
+    pub rates: Vec<(&'static str, Option<f64>)>, // Mints per hour for each window, None without enough history
+    pub eta_secs: Option<u64>, // Estimated seconds until the max supply is minted
+    pub stalled_for: Option<u64>, // Seconds since the last new mint, when minting has stalled
+    pub finished: bool, // The max supply is fully minted
+}
+
+// Work out the mint velocity from the token's current state and its history (oldest sample first)
+pub fn mint_velocity(token: &ResultStruct, history: &[HistorySample], now: u64) -> MintVelocity {
+    // A field the API left out or sent malformed is unknown, a zero max or limit tells nothing either
+    let decimals = TokenAmount::parse_decimals(&token.dec);
+    let amount = |raw: &str| decimals.clone().and_then(|dec| TokenAmount::parse(raw, dec)).ok().map(|amount| amount.raw());
+    let max = amount(&token.max).filter(|max| *max > 0);
+    let lim = amount(&token.lim).filter(|lim| *lim > 0);
+    let minted = amount(&token.minted);
+    let finished = matches!((max, minted), (Some(max), Some(minted)) if minted >= max);
+
+    let current = HistorySample::from_token(token, now);
+
+    let rates: Vec<(&'static str, Option<f64>)> = WINDOWS.iter()
+        .map(|(label, length)| (*label, window_rate(history, &current, *length)))
+        .collect();
+
+    // Prefer the 24h rate for the estimate, it smooths out bursts without lagging a week behind
+    let rate = rates.get(1).and_then(|(_, rate)| *rate)
+        .or_else(|| rates[0].1)
+        .or_else(|| rates[2].1);
+    let remaining = max.zip(minted).and_then(|(max, minted)| max.checked_sub(minted)).filter(|remaining| *remaining > 0);
+    let eta_secs = match (rate, remaining, lim) {
+        (Some(rate), Some(remaining), Some(lim)) if rate > 0.0 => {
+            let remaining_mints = remaining as f64 / lim as f64;
+            Some((remaining_mints / rate * HOUR as f64) as u64)
+        },
+        _ => None,
+    };
+
+    let stalled_for = if finished {
+        None
+    } else {
+        last_mint_time(history, &current)
+            .map(|last_mint| now.saturating_sub(last_mint))
+            .filter(|idle| *idle >= STALL_AFTER)
+    };
+
+    MintVelocity { rates, eta_secs, stalled_for, finished }
+}
+
+// Mints per hour between the oldest sample inside the window and now
+fn window_rate(history: &[HistorySample], current: &HistorySample, length: u64) -> Option<f64> {
+    let start = current.taken_at.saturating_sub(length);
+    let first = history.iter().find(|sample| sample.taken_at >= start)?;
+    let span = current.taken_at.saturating_sub(first.taken_at);
+    if (span as f64) < length as f64 * MIN_COVERAGE {
+        return None;
+    }
+
+    let mints = current.mint_total?.saturating_sub(first.mint_total?);
+    Some(mints as f64 / (span as f64 / HOUR as f64))
+}
+
+// When the minted amount last went up; None when the history is too short to tell
+fn last_mint_time(history: &[HistorySample], current: &HistorySample) -> Option<u64> {
+    let oldest = history.first()?;
+    let mut last_change = None;
+    let mut previous = oldest;
+    for sample in history.iter().skip(1).chain(std::iter::once(current)) {
+        if sample.minted > previous.minted {
+            last_change = Some(sample.taken_at);
+        }
+        previous = sample;
+    }
+    // Without any change we only know minting stopped before the oldest sample
+    Some(last_change.unwrap_or(oldest.taken_at))
+}
+
+// Human readable duration, e.g. "3 days 4 hr" or "25 min"
+pub fn format_duration(secs: u64) -> String {
+    let days = secs / 86400;
+    let hours = secs % 86400 / HOUR;
+    let minutes = secs % HOUR / 60;
+
+    if days > 0 {
+        format!("{} days {} hr", days, hours)
+    } else if hours > 0 {
+        format!("{} hr {} min", hours, minutes)
+    } else {
+        format!("{} min", minutes.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 100 * 24 * HOUR;
+
+    // A token with no decimals minting 10 tokens per mint
+    fn token(max: &str, minted: &str, mints: u64) -> ResultStruct {
+        ResultStruct {
+            tick: "TEST".to_string(),
+            max: max.to_string(),
+            lim: "10".to_string(),
+            dec: "0".to_string(),
+            minted: minted.to_string(),
+            mintTotal: Some(mints.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn sample(hours_ago: u64, minted: u128, mints: u64) -> HistorySample {
+        HistorySample { taken_at: NOW - hours_ago * HOUR, minted, mint_total: Some(mints), holder_total: None }
+    }
+
+    fn rate(velocity: &MintVelocity, label: &str) -> Option<f64> {
+        velocity.rates.iter().find(|(window, _)| *window == label).and_then(|(_, rate)| *rate)
+    }
+
+    #[test]
+    fn no_history_tells_nothing() {
+        let velocity = mint_velocity(&token("1000", "400", 40), &[], NOW);
+        assert!(velocity.rates.iter().all(|(_, rate)| rate.is_none()));
+        assert_eq!(velocity.eta_secs, None);
+        assert_eq!(velocity.stalled_for, None);
+        assert!(!velocity.finished);
+    }
+
+    #[test]
+    fn single_snapshot_only_covers_the_short_window() {
+        let velocity = mint_velocity(&token("1000", "400", 40), &[sample(1, 300, 30)], NOW);
+        assert_eq!(rate(&velocity, "1h"), Some(10.0));
+        assert_eq!(rate(&velocity, "24h"), None);
+        assert_eq!(rate(&velocity, "7d"), None);
+    }
+
+    #[test]
+    fn windows_need_samples_over_half_their_length() {
+        // Eleven hours of history is too little for the 24h window, thirteen is enough
+        let short = mint_velocity(&token("1000", "400", 40), &[sample(11, 290, 29)], NOW);
+        assert_eq!(rate(&short, "24h"), None);
+        let long = mint_velocity(&token("1000", "400", 40), &[sample(13, 270, 27)], NOW);
+        assert_eq!(rate(&long, "24h"), Some(1.0));
+    }
+
+    #[test]
+    fn eta_uses_the_daily_rate() {
+        // One mint of 10 tokens per hour over the last day, 600 tokens or 60 mints left
+        let history = [sample(24, 160, 16), sample(12, 280, 28), sample(1, 390, 39)];
+        let velocity = mint_velocity(&token("1000", "400", 40), &history, NOW);
+        assert_eq!(rate(&velocity, "24h"), Some(1.0));
+        assert_eq!(velocity.eta_secs, Some(60 * HOUR));
+        assert_eq!(velocity.stalled_for, None);
+    }
+
+    #[test]
+    fn stalled_token_reports_the_idle_time() {
+        let history = [sample(10, 300, 30), sample(8, 400, 40), sample(4, 400, 40)];
+        let velocity = mint_velocity(&token("1000", "400", 40), &history, NOW);
+        assert_eq!(velocity.stalled_for, Some(8 * HOUR));
+        assert_eq!(rate(&velocity, "1h"), None);
+        assert!(!velocity.finished);
+    }
+
+    #[test]
+    fn finished_token_has_no_eta_and_never_stalls() {
+        let history = [sample(48, 900, 90), sample(30, 1000, 100)];
+        let velocity = mint_velocity(&token("1000", "1000", 100), &history, NOW);
+        assert!(velocity.finished);
+        assert_eq!(velocity.eta_secs, None);
+        assert_eq!(velocity.stalled_for, None);
+    }
+
+    #[test]
+    fn unknown_max_has_no_eta() {
+        let history = [sample(24, 160, 16)];
+        let velocity = mint_velocity(&token("", "400", 40), &history, NOW);
+        assert_eq!(rate(&velocity, "24h"), Some(1.0));
+        assert_eq!(velocity.eta_secs, None);
+        assert!(!velocity.finished);
+    }
+}
@@ -0,0 +1,118 @@
+// src/networks.rs
+use crate::imports::*;
+use crate::config::Config;
+use crate::hot_reload::ConfigError;
+use crate::kasplex::KasplexClient;
+
+pub const NETWORKS_FILE: &str = "networks.json"; // Kasplex networks the bot can query
+
+#[derive(Debug, Deserialize)]
+struct NetworkSpec {
+    name: String,
+    label: String,
+    base_url: String,
+    #[serde(default = "default_address_prefix")]
+    address_prefix: String,
+    #[serde(default)]
+    explorer_address_url: Option<String>,
+}
+
+// Networks listed before the prefix was configurable were all testnets
+fn default_address_prefix() -> String {
+    "kaspatest".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+struct NetworksFile {
+    default: String,
+    networks: Vec<NetworkSpec>,
+}
+
+// A Kasplex network with its own API client and cache namespace
+#[derive(Debug)]
+pub struct Network {
+    pub name: String,  // Identifier used in `--net` and in the cache keys
+    pub label: String, // Display name shown in the embeds
+    pub address_prefix: String, // `kaspa` or `kaspatest`, what addresses on this network start with
+    pub explorer_address_url: Option<String>, // Block explorer page of an address is this URL followed by the address
+    pub client: KasplexClient,
+}
+
+impl Network {
+    // Markdown link to the explorer page of an address, showing the shortened address
+    pub fn address_link(&self, address: &str) -> String {
+        let short = shorten_address(address);
+        match &self.explorer_address_url {
+            Some(url) => format!("[{}]({}{})", short, url, address),
+            None => format!("`{}`", short),
+        }
+    }
+}
+
+// Keep the prefix with the start and end of the payload, e.g. kaspa:qrt3lf…uf8qfr
+pub fn shorten_address(address: &str) -> String {
+    let (prefix, payload) = address.split_once(':').unwrap_or(("", address));
+    let chars: Vec<char> = payload.chars().collect();
+    if chars.len() <= 14 {
+        return address.to_string();
+    }
+    let start: String = chars[..6].iter().collect();
+    let end: String = chars[chars.len() - 6..].iter().collect();
+    match prefix {
+        "" => format!("{}…{}", start, end),
+        _ => format!("{}:{}…{}", prefix, start, end),
+    }
+}
+
+#[derive(Debug)]
+pub struct Networks {
+    default: String,
+    networks: Vec<Network>,
+}
+
+impl Networks {
+    // Load networks.json, creating one API client per network.
+    // KASPLEX_API_BASE_URL still overrides the default network's URL for existing deployments.
+    pub fn load(config: &Config) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(NETWORKS_FILE).map_err(ConfigError::Read)?;
+        let file: NetworksFile = serde_json::from_str(&content)?;
+
+        if file.networks.is_empty() {
+            return Err(ConfigError::Invalid("no networks configured".to_string()));
+        }
+        if !file.networks.iter().any(|network| network.name == file.default) {
+            return Err(ConfigError::Invalid(format!("default network {} is not in the list", file.default)));
+        }
+
+        let networks = file.networks.into_iter()
+            .map(|spec| {
+                let base_url = match (&config.api_base_url, spec.name == file.default) {
+                    (Some(override_url), true) => override_url.clone(),
+                    _ => spec.base_url,
+                };
+                Network {
+                    client: KasplexClient::new(&base_url, config.retry.clone(), config.circuit_breaker()),
+                    name: spec.name,
+                    label: spec.label,
+                    address_prefix: spec.address_prefix,
+                    explorer_address_url: spec.explorer_address_url,
+                }
+            })
+            .collect();
+
+        Ok(Networks { default: file.default, networks })
+    }
+
+    // Look a network up by name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&Network> {
+        self.networks.iter().find(|network| network.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn default_network(&self) -> &Network {
+        self.get(&self.default).expect("Default network is checked on load")
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.networks.iter().map(|network| network.name.as_str()).collect()
+    }
+}
@@ -0,0 +1,230 @@
+// src/rate_limit.rs
+use serenity::model::id::{GuildId, RoleId, UserId};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60); // How often buckets that have refilled are dropped
+
+// Size and refill speed of a token bucket; a burst of 0 turns the limit off
+#[derive(Debug, Clone, Copy)]
+pub struct BucketLimit {
+    pub burst: u32,      // Commands that can be used back to back
+    pub per_minute: f64, // Commands regained per minute
+}
+
+// Limits from the config: one bucket per user and command, one per guild and command
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    pub user: BucketLimit,
+    pub guild: BucketLimit,
+    pub exempt_roles: Vec<u64>, // Members with one of these roles are never limited
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BucketKey {
+    User(u64, &'static str),
+    Guild(u64, &'static str),
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    warned: bool, // The user was already told to wait for this bucket
+}
+
+impl Bucket {
+    // Add the tokens regained since the last update, up to the burst size
+    fn refill(&mut self, limit: &BucketLimit, now: Instant) {
+        let regained = now.duration_since(self.updated).as_secs_f64() * limit.per_minute / 60.0;
+        self.tokens = (self.tokens + regained).min(limit.burst as f64);
+        self.updated = now;
+    }
+
+    // How long until a whole token is available
+    fn wait(&self, limit: &BucketLimit) -> Duration {
+        if self.tokens >= 1.0 {
+            return Duration::ZERO;
+        }
+        if limit.per_minute <= 0.0 {
+            return Duration::MAX;
+        }
+        Duration::from_secs_f64((1.0 - self.tokens) * 60.0 / limit.per_minute)
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    buckets: HashMap<BucketKey, Bucket>,
+    last_prune: Option<Instant>,
+}
+
+// Token-bucket rate limiter for commands
+#[derive(Debug)]
+pub struct RateLimiter {
+    limits: RateLimits,
+    state: Mutex<LimiterState>,
+}
+
+// Returned when a command has to wait
+#[derive(Debug, Clone, Copy)]
+pub struct Cooldown {
+    pub retry_after: Duration,
+    pub first: bool, // The first refusal for this cooldown; later ones can be ignored quietly
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        RateLimiter { limits, state: Mutex::new(LimiterState::default()) }
+    }
+
+    // Take a token from the user's and the guild's bucket for this command, or say how long to wait.
+    // Tokens are only taken when both buckets have one, so a refused command costs nothing.
+    pub fn check(&self, command: &'static str, user_id: UserId, guild_id: Option<GuildId>, roles: &[RoleId], now: Instant) -> Result<(), Cooldown> {
+        if roles.iter().any(|role| self.limits.exempt_roles.contains(&role.get())) {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().unwrap();
+        self.prune(&mut state, now);
+
+        let mut keys = vec![(BucketKey::User(user_id.get(), command), self.limits.user)];
+        if let Some(guild_id) = guild_id {
+            keys.push((BucketKey::Guild(guild_id.get(), command), self.limits.guild));
+        }
+        keys.retain(|(_, limit)| limit.burst > 0);
+
+        let mut wait = Duration::ZERO;
+        for (key, limit) in &keys {
+            let bucket = state.buckets.entry(key.clone()).or_insert(Bucket { tokens: limit.burst as f64, updated: now, warned: false });
+            bucket.refill(limit, now);
+            wait = wait.max(bucket.wait(limit));
+        }
+
+        if wait > Duration::ZERO {
+            let mut first = false;
+            for (key, limit) in &keys {
+                if let Some(bucket) = state.buckets.get_mut(key).filter(|bucket| bucket.wait(limit) > Duration::ZERO) {
+                    first |= !bucket.warned;
+                    bucket.warned = true;
+                }
+            }
+            return Err(Cooldown { retry_after: wait, first });
+        }
+
+        for (key, _) in &keys {
+            if let Some(bucket) = state.buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+                bucket.warned = false;
+            }
+        }
+        Ok(())
+    }
+
+    // Drop buckets that are full again, they behave the same as new ones
+    fn prune(&self, state: &mut LimiterState, now: Instant) {
+        if state.last_prune.is_some_and(|last| now.duration_since(last) < PRUNE_INTERVAL) {
+            return;
+        }
+        let limits = &self.limits;
+        state.buckets.retain(|key, bucket| {
+            let limit = match key {
+                BucketKey::User(..) => &limits.user,
+                BucketKey::Guild(..) => &limits.guild,
+            };
+            bucket.refill(limit, now);
+            bucket.tokens < limit.burst as f64
+        });
+        state.last_prune = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: UserId = UserId::new(1);
+    const OTHER_USER: UserId = UserId::new(2);
+    const GUILD: GuildId = GuildId::new(10);
+
+    fn limiter(user: u32, guild: u32) -> RateLimiter {
+        RateLimiter::new(RateLimits {
+            user: BucketLimit { burst: user, per_minute: 6.0 },
+            guild: BucketLimit { burst: guild, per_minute: 6.0 },
+            exempt_roles: vec![99],
+        })
+    }
+
+    #[test]
+    fn burst_runs_out_and_refills() {
+        let limiter = limiter(2, 0);
+        let start = Instant::now();
+        assert!(limiter.check("status", USER, None, &[], start).is_ok());
+        assert!(limiter.check("status", USER, None, &[], start).is_ok());
+
+        let cooldown = limiter.check("status", USER, None, &[], start).unwrap_err();
+        assert_eq!(cooldown.retry_after, Duration::from_secs(10));
+        // Other commands have their own bucket
+        assert!(limiter.check("chart", USER, None, &[], start).is_ok());
+
+        assert!(limiter.check("status", USER, None, &[], start + Duration::from_secs(9)).is_err());
+        assert!(limiter.check("status", USER, None, &[], start + Duration::from_secs(10)).is_ok());
+    }
+
+    #[test]
+    fn refused_command_keeps_the_other_buckets_token() {
+        let limiter = limiter(1, 3);
+        let now = Instant::now();
+        assert!(limiter.check("status", USER, Some(GUILD), &[], now).is_ok());
+        // Refused by the user's bucket, the guild's bucket keeps its two tokens
+        assert!(limiter.check("status", USER, Some(GUILD), &[], now).is_err());
+        assert!(limiter.check("status", USER, Some(GUILD), &[], now).is_err());
+
+        assert!(limiter.check("status", OTHER_USER, Some(GUILD), &[], now).is_ok());
+        assert!(limiter.check("status", UserId::new(3), Some(GUILD), &[], now).is_ok());
+        assert!(limiter.check("status", UserId::new(4), Some(GUILD), &[], now).is_err());
+    }
+
+    #[test]
+    fn first_refusal_is_flagged_once_per_cooldown() {
+        let limiter = limiter(1, 0);
+        let start = Instant::now();
+        assert!(limiter.check("status", USER, None, &[], start).is_ok());
+        assert!(limiter.check("status", USER, None, &[], start).unwrap_err().first);
+        assert!(!limiter.check("status", USER, None, &[], start + Duration::from_secs(5)).unwrap_err().first);
+
+        // Once a command goes through, the next cooldown is announced again
+        let later = start + Duration::from_secs(10);
+        assert!(limiter.check("status", USER, None, &[], later).is_ok());
+        assert!(limiter.check("status", USER, None, &[], later).unwrap_err().first);
+    }
+
+    #[test]
+    fn exempt_roles_and_zero_burst_are_never_limited() {
+        let now = Instant::now();
+        let exempt = limiter(1, 1);
+        let unlimited = limiter(0, 0);
+        for _ in 0..5 {
+            assert!(exempt.check("status", USER, Some(GUILD), &[RoleId::new(5), RoleId::new(99)], now).is_ok());
+            assert!(unlimited.check("status", USER, Some(GUILD), &[], now).is_ok());
+        }
+        assert!(exempt.check("status", USER, Some(GUILD), &[RoleId::new(5)], now).is_ok());
+        assert!(exempt.check("status", USER, Some(GUILD), &[RoleId::new(5)], now).is_err());
+    }
+
+    #[test]
+    fn full_buckets_are_pruned() {
+        let limiter = limiter(1, 1);
+        let start = Instant::now();
+        assert!(limiter.check("status", USER, Some(GUILD), &[], start).is_ok());
+        assert_eq!(limiter.state.lock().unwrap().buckets.len(), 2);
+
+        // Refilled after ten seconds, but only dropped on the next prune a minute later
+        assert!(limiter.check("chart", USER, None, &[], start + Duration::from_secs(30)).is_ok());
+        assert_eq!(limiter.state.lock().unwrap().buckets.len(), 3);
+        assert!(limiter.check("chart", OTHER_USER, None, &[], start + PRUNE_INTERVAL).is_ok());
+        let buckets = limiter.state.lock().unwrap().buckets.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(buckets, vec![BucketKey::User(2, "chart")]);
+    }
+}
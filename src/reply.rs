@@ -0,0 +1,75 @@
+// src/reply.rs
+use crate::imports::*;
+
+// A command response that can be delivered as a channel message (prefix commands)
+// or as an interaction follow-up (slash commands)
+#[derive(Default)]
+pub struct Reply {
+    pub content: Option<String>,
+    pub embeds: Vec<CreateEmbed>,
+    pub files: Vec<CreateAttachment>, // Files uploaded with the message, e.g. chart images
+    pub components: Vec<CreateActionRow>, // Buttons under the message
+}
+
+impl Reply {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    pub fn embed(mut self, embed: CreateEmbed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    pub fn attachment(mut self, file: CreateAttachment) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    pub fn components(mut self, components: Vec<CreateActionRow>) -> Self {
+        self.components = components;
+        self
+    }
+
+    // Build the payload for a regular channel message
+    pub fn into_message(self) -> CreateMessage {
+        let mut message = CreateMessage::new().embeds(self.embeds).add_files(self.files).components(self.components);
+        if let Some(content) = self.content {
+            message = message.content(content);
+        }
+        message
+    }
+
+    // Build the payload for a follow-up to a deferred slash command
+    pub fn into_followup(self) -> CreateInteractionResponseFollowup {
+        let mut followup = CreateInteractionResponseFollowup::new().embeds(self.embeds).add_files(self.files).components(self.components);
+        if let Some(content) = self.content {
+            followup = followup.content(content);
+        }
+        followup
+    }
+
+    // Build the payload that replaces a message after one of its buttons was pressed
+    pub fn into_edit(self) -> EditInteractionResponse {
+        let mut edit = EditInteractionResponse::new().embeds(self.embeds).components(self.components);
+        for file in self.files {
+            edit = edit.new_attachment(file);
+        }
+        if let Some(content) = self.content {
+            edit = edit.content(content);
+        }
+        edit
+    }
+
+    // Send the reply to a channel, logging any failure
+    pub async fn send(self, ctx: &Context, channel_id: ChannelId) {
+        if let Err(why) = channel_id.send_message(ctx.http.clone(), self.into_message()).await {
+            println!("Error sending message: {:?}", why);
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Holder {
     pub address: String,
     pub amount: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ResultStruct {
     pub tick: String,
     pub max: String,
@@ -26,8 +28,56 @@ pub struct ResultStruct {
     pub holder: Option<Vec<Holder>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataStruct {
     pub message: String,
     pub result: Vec<ResultStruct>,
 }
+
+// Balance of one KRC20 token held by an address
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenInfo {
+    pub tick: String,
+    pub balance: String,
+    pub locked: String,
+    pub dec: String,
+    pub opScoreMod: String,
+}
+
+// Indexer status returned by /info
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexerInfo {
+    pub daaScore: String,
+    pub daaScoreGap: String,
+    pub opScore: String,
+    pub opTotal: String,
+    pub tokenTotal: String,
+    pub feeTotal: String,
+}
+
+// A KRC20 operation (deploy, mint, transfer) returned by /oplist and /op/{id}
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Operation {
+    pub p: String,
+    pub op: String,
+    pub tick: String,
+    pub max: String,
+    pub lim: String,
+    pub pre: String,
+    pub dec: String,
+    pub amt: String,
+    pub from: String,
+    pub to: String,
+    pub opScore: String,
+    pub hashRev: String,
+    pub feeRev: String,
+    pub txAccept: String,
+    pub opAccept: String,
+    pub opError: String,
+    pub checkpoint: String,
+    pub mtsAdd: String,
+    pub mtsMod: String,
+}
@@ -0,0 +1,173 @@
+// src/retry.rs
+use rand::Rng;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// How often and how patiently a failed request is retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration, // Delay before the first retry, doubled on each further attempt
+    pub max_delay: Duration,  // Upper bound for a single delay
+}
+
+impl RetryPolicy {
+    // Exponential backoff with full jitter: a random delay between zero and the capped exponential value
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponential = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        let capped = exponential.min(self.max_delay);
+        let millis = capped.as_millis() as u64;
+        if millis == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>, // Set while the breaker is open
+    trial_started_at: Option<Instant>, // Set while the one trial request after the cooldown is in flight
+}
+
+// Stops calling an upstream service after repeated failures, then lets a single trial request through after a cooldown.
+// Everything else is rejected until the trial succeeds (closed again) or fails (open for another cooldown).
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    // Whether a request may be sent: the breaker is closed, or it is the trial request once the cooldown is over.
+    // A trial that never reports back, e.g. because its caller went away, is replaced after another cooldown.
+    pub fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let opened_at = match state.opened_at {
+            Some(opened_at) => opened_at,
+            None => return true,
+        };
+        if opened_at.elapsed() < self.cooldown {
+            return false;
+        }
+        if state.trial_started_at.is_some_and(|started| started.elapsed() < self.cooldown) {
+            return false; // Half open, and the trial request is still running
+        }
+        state.trial_started_at = Some(Instant::now());
+        true
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.opened_at.is_some() {
+            println!("Circuit breaker closed, the API is responding again");
+        }
+        *state = BreakerState::default();
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.failure_threshold {
+            if state.opened_at.is_none() {
+                println!("Circuit breaker opened after {} consecutive failures", state.consecutive_failures);
+            }
+            state.opened_at = Some(Instant::now()); // A failed trial request restarts the cooldown
+            state.trial_started_at = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    const COOLDOWN: Duration = Duration::from_millis(100);
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy { max_retries: 5, base_delay: Duration::from_millis(100), max_delay: Duration::from_secs(1) }
+    }
+
+    fn open_breaker() -> CircuitBreaker {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        breaker
+    }
+
+    #[test]
+    fn delay_doubles_up_to_the_cap_with_jitter() {
+        let policy = policy();
+        for (attempt, bound) in [(0, 100), (1, 200), (3, 800), (4, 1000), (40, 1000)] {
+            let delays: Vec<Duration> = (0..200).map(|_| policy.delay(attempt)).collect();
+            assert!(delays.iter().all(|delay| *delay <= Duration::from_millis(bound)), "attempt {}", attempt);
+            // Full jitter spreads the delays over the whole range instead of always waiting the maximum
+            assert!(delays.iter().any(|delay| *delay > Duration::from_millis(bound / 2)), "attempt {}", attempt);
+            assert!(delays.iter().any(|delay| *delay < Duration::from_millis(bound / 2)), "attempt {}", attempt);
+        }
+
+        let instant = RetryPolicy { base_delay: Duration::ZERO, ..policy };
+        assert_eq!(instant.delay(3), Duration::ZERO);
+    }
+
+    #[test]
+    fn breaker_opens_at_the_threshold() {
+        let breaker = CircuitBreaker::new(3, COOLDOWN);
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.allow());
+        // A success in between starts the count over
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.allow());
+        breaker.record_failure();
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn half_open_breaker_allows_one_trial() {
+        let breaker = open_breaker();
+        assert!(!breaker.allow());
+        sleep(COOLDOWN);
+        assert!(breaker.allow());
+        assert!(!breaker.allow());
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn failed_trial_reopens_the_breaker() {
+        let breaker = open_breaker();
+        sleep(COOLDOWN);
+        assert!(breaker.allow());
+        breaker.record_failure();
+        assert!(!breaker.allow());
+        sleep(COOLDOWN);
+        assert!(breaker.allow());
+    }
+
+    #[test]
+    fn successful_trial_closes_the_breaker() {
+        let breaker = open_breaker();
+        sleep(COOLDOWN);
+        assert!(breaker.allow());
+        breaker.record_success();
+        assert!(breaker.allow());
+        assert!(breaker.allow());
+        // Closed means the full threshold of failures is needed to open it again
+        breaker.record_failure();
+        assert!(breaker.allow());
+    }
+}
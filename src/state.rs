@@ -0,0 +1,20 @@
+// src/state.rs
+use crate::cache::Cache;
+use crate::commands::registry::Registry;
+use crate::config::Config;
+use crate::guild_settings::GuildSettingsStore;
+use crate::networks::Networks;
+use crate::rate_limit::RateLimiter;
+use crate::storage::Storage;
+use std::sync::Arc;
+
+// Long-lived services shared by the event handler and every command
+pub struct BotState {
+    pub config: Config,
+    pub networks: Networks,
+    pub guild_settings: GuildSettingsStore,
+    pub cache: Arc<dyn Cache>,
+    pub storage: Storage, // Token history in SQLite
+    pub registry: Registry,
+    pub rate_limiter: RateLimiter, // Cooldowns per user and guild for every command
+}
@@ -0,0 +1,861 @@
+// src/storage.rs
+use crate::imports::*;
+use crate::cache::{CacheKey, Endpoint, Entry};
+use crate::result_struct::{Holder, ResultStruct, TokenInfo};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+// Schema changes, applied in order; PRAGMA user_version records how many have run
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE token_snapshots (
+        id INTEGER PRIMARY KEY,
+        network TEXT NOT NULL,
+        tick TEXT NOT NULL,
+        taken_at INTEGER NOT NULL,
+        state TEXT NOT NULL,
+        max TEXT NOT NULL,
+        lim TEXT NOT NULL,
+        pre TEXT NOT NULL,
+        dec TEXT NOT NULL,
+        minted TEXT NOT NULL,
+        holder_total INTEGER,
+        mint_total INTEGER,
+        transfer_total INTEGER
+    );
+    CREATE INDEX token_snapshots_by_tick ON token_snapshots (network, tick, taken_at);
+    CREATE TABLE snapshot_holders (
+        snapshot_id INTEGER NOT NULL REFERENCES token_snapshots (id) ON DELETE CASCADE,
+        rank INTEGER NOT NULL,
+        address TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        PRIMARY KEY (snapshot_id, rank)
+    );
+    CREATE TABLE cache_entries (
+        network TEXT NOT NULL,
+        endpoint TEXT NOT NULL,
+        id TEXT NOT NULL,
+        value TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL,
+        PRIMARY KEY (network, endpoint, id)
+    );",
+    "CREATE TABLE subscriptions (
+        channel_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        network TEXT NOT NULL,
+        tick TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        created_at INTEGER NOT NULL,
+        last_milestone INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (channel_id, network, tick, kind)
+    );
+    CREATE INDEX subscriptions_by_kind ON subscriptions (kind, network, tick);",
+    "CREATE TABLE deployment_feeds (
+        channel_id INTEGER NOT NULL,
+        guild_id INTEGER NOT NULL,
+        network TEXT NOT NULL,
+        min_supply TEXT NOT NULL,
+        max_pre INTEGER,
+        created_at INTEGER NOT NULL,
+        PRIMARY KEY (channel_id, network)
+    );
+    CREATE TABLE deployment_cursors (
+        network TEXT PRIMARY KEY,
+        op_score TEXT NOT NULL,
+        updated_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE watched_addresses (
+        user_id INTEGER NOT NULL,
+        network TEXT NOT NULL,
+        address TEXT NOT NULL,
+        label TEXT,
+        created_at INTEGER NOT NULL,
+        balances TEXT,
+        failures INTEGER NOT NULL DEFAULT 0,
+        next_check INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (user_id, network, address)
+    );",
+    "ALTER TABLE subscriptions ADD COLUMN last_snapshot INTEGER NOT NULL DEFAULT 0;",
+    "CREATE TABLE token_views (
+        network TEXT NOT NULL,
+        tick TEXT NOT NULL,
+        last_viewed_at INTEGER NOT NULL,
+        PRIMARY KEY (network, tick)
+    );
+    INSERT INTO token_views (network, tick, last_viewed_at)
+        SELECT network, tick, MAX(taken_at) FROM token_snapshots GROUP BY network, tick;",
+];
+
+#[derive(Debug)]
+pub enum StorageError {
+    Sqlite(rusqlite::Error),
+    Task(String), // The blocking database task panicked or was cancelled
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Sqlite(e) => write!(f, "database error: {}", e),
+            StorageError::Task(reason) => write!(f, "database task failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<rusqlite::Error> for StorageError {
+    fn from(e: rusqlite::Error) -> Self {
+        StorageError::Sqlite(e)
+    }
+}
+
+// One recorded state of a token, as fetched from the API
+#[derive(Debug, Clone)]
+pub struct TokenSnapshot {
+    pub id: i64,
+    pub taken_at: u64, // Seconds since the UNIX epoch
+    pub token: ResultStruct, // Token fields as they were at that time, including the top holders
+}
+
+// Counters of a token at one point in time
+#[derive(Debug, Clone)]
+pub struct HistorySample {
+    pub taken_at: u64,
+    pub minted: u128, // Raw amount, without applying the decimals
+    pub mint_total: Option<u64>,
+    pub holder_total: Option<u64>,
+}
+
+impl HistorySample {
+    // Sample of a token's current state, to extend the recorded history up to now
+    pub fn from_token(token: &ResultStruct, taken_at: u64) -> Self {
+        HistorySample {
+            taken_at,
+            minted: token.minted.parse().unwrap_or(0),
+            mint_total: token.mintTotal.as_deref().and_then(|total| total.parse().ok()),
+            holder_total: token.holderTotal.as_deref().and_then(|total| total.parse().ok()),
+        }
+    }
+}
+
+// What a channel asked to be told about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionKind {
+    Milestones, // Mint progress crossing the configured percentages
+    Whales,     // Large moves among the top holders
+}
+
+impl SubscriptionKind {
+    pub const NAMES: &'static [&'static str] = &["milestones", "whales"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SubscriptionKind::Milestones => "milestones",
+            SubscriptionKind::Whales => "whales",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "milestones" => Some(SubscriptionKind::Milestones),
+            "whales" => Some(SubscriptionKind::Whales),
+            _ => None,
+        }
+    }
+}
+
+// A channel's subscription to alerts about one token
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub channel_id: u64,
+    pub guild_id: u64,
+    pub network: String,
+    pub tick: String,
+    pub kind: SubscriptionKind,
+    pub last_milestone: u32, // Highest mint percentage already announced, so alerts survive restarts without repeating
+    pub last_snapshot: i64,  // Newest snapshot whose top holders were compared for whale alerts
+}
+
+// A channel that gets an announcement for every new token on a network that passes its filters
+#[derive(Debug, Clone)]
+pub struct DeploymentFeed {
+    pub channel_id: u64,
+    pub guild_id: u64,
+    pub network: String,
+    pub min_supply: u128,     // Smallest max supply announced, in whole tokens
+    pub max_pre: Option<u32>, // Largest pre-allocation announced, in hundredths of a percent of the max supply
+}
+
+// An address a user gets a DM about when its balances change
+#[derive(Debug, Clone)]
+pub struct WatchedAddress {
+    pub user_id: u64,
+    pub network: String,
+    pub address: String,
+    pub label: Option<String>,
+    pub balances: Option<Vec<TokenInfo>>, // Balances at the last successful check, None until the first one
+    pub failures: u32,                    // Failed checks in a row, used for the backoff
+    pub next_check: u64,                  // Not checked again before this time
+}
+
+// Local SQLite database for token history and the cache snapshot
+#[derive(Clone)]
+pub struct Storage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Storage {
+    // Open or create the database file and bring the schema up to date
+    pub fn open(path: &str) -> Result<Self, StorageError> {
+        if let Some(folder) = Path::new(path).parent().filter(|folder| !folder.as_os_str().is_empty()) {
+            create_dir_all(folder).map_err(|e| StorageError::Task(e.to_string()))?;
+        }
+
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?; // Readers do not block the writer
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut conn)?;
+
+        Ok(Storage { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    // Run a query on the blocking thread pool so the Discord event loop never waits on disk
+    async fn run<T, F>(&self, query: F) -> Result<T, StorageError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap();
+            query(&mut conn)
+        })
+        .await
+        .map_err(|e| StorageError::Task(e.to_string()))?
+        .map_err(StorageError::from)
+    }
+
+    // Record a token's state, with its top holders when the token carries them
+    pub async fn record_snapshot(&self, network: &str, token: &ResultStruct, taken_at: u64) -> Result<(), StorageError> {
+        let network = network.to_string();
+        let token = token.clone();
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO token_snapshots
+                    (network, tick, taken_at, state, max, lim, pre, dec, minted, holder_total, mint_total, transfer_total)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    network,
+                    token.tick.to_uppercase(),
+                    taken_at as i64,
+                    token.state,
+                    token.max,
+                    token.lim,
+                    token.pre,
+                    token.dec,
+                    token.minted,
+                    parse_count(&token.holderTotal),
+                    parse_count(&token.mintTotal),
+                    parse_count(&token.transferTotal),
+                ],
+            )?;
+            let snapshot_id = tx.last_insert_rowid();
+            for (rank, holder) in token.holder.iter().flatten().enumerate() {
+                tx.execute(
+                    "INSERT INTO snapshot_holders (snapshot_id, rank, address, amount) VALUES (?1, ?2, ?3, ?4)",
+                    params![snapshot_id, rank as i64, holder.address, holder.amount],
+                )?;
+            }
+            tx.commit()
+        })
+        .await
+    }
+
+    // The most recent snapshot of a token, with the top holders of the most recent snapshot that recorded them
+    pub async fn latest_snapshot(&self, network: &str, tick: &str) -> Result<Option<TokenSnapshot>, StorageError> {
+        let network = network.to_string();
+        let tick = tick.to_uppercase();
+        self.run(move |conn| {
+            let snapshot = conn.query_row(
+                &format!("{} WHERE network = ?1 AND tick = ?2 ORDER BY taken_at DESC, id DESC LIMIT 1", SNAPSHOT_SELECT),
+                params![network, tick],
+                read_snapshot,
+            )
+            .optional()?;
+            let Some(mut snapshot) = snapshot else {
+                return Ok(None);
+            };
+            let holders_id: Option<i64> = conn.query_row(
+                "SELECT MAX(snapshot_id) FROM snapshot_holders
+                 JOIN token_snapshots ON token_snapshots.id = snapshot_holders.snapshot_id
+                 WHERE network = ?1 AND tick = ?2",
+                params![network, tick],
+                |row| row.get(0),
+            )?;
+            snapshot.token.holder = Some(match holders_id {
+                Some(id) => read_holders(conn, id)?,
+                None => Vec::new(),
+            });
+            Ok(Some(snapshot))
+        })
+        .await
+    }
+
+    // The most recent snapshot of a token that recorded its top holders
+    pub async fn latest_holder_snapshot(&self, network: &str, tick: &str) -> Result<Option<TokenSnapshot>, StorageError> {
+        let network = network.to_string();
+        let tick = tick.to_uppercase();
+        self.run(move |conn| {
+            let snapshot = conn.query_row(
+                &format!(
+                    "{} WHERE network = ?1 AND tick = ?2 AND EXISTS (SELECT 1 FROM snapshot_holders WHERE snapshot_id = token_snapshots.id)
+                     ORDER BY taken_at DESC, id DESC LIMIT 1",
+                    SNAPSHOT_SELECT,
+                ),
+                params![network, tick],
+                read_snapshot,
+            )
+            .optional()?;
+            with_holders(conn, snapshot)
+        })
+        .await
+    }
+
+    // A snapshot by its id, with its top holders
+    pub async fn snapshot(&self, id: i64) -> Result<Option<TokenSnapshot>, StorageError> {
+        self.run(move |conn| {
+            let snapshot = conn.query_row(&format!("{} WHERE id = ?1", SNAPSHOT_SELECT), [id], read_snapshot).optional()?;
+            with_holders(conn, snapshot)
+        })
+        .await
+    }
+
+    // Minted amount, mint count and holder count of a token since a point in time, oldest first
+    pub async fn token_history(&self, network: &str, tick: &str, since: u64) -> Result<Vec<HistorySample>, StorageError> {
+        let network = network.to_string();
+        let tick = tick.to_uppercase();
+        self.run(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT taken_at, minted, mint_total, holder_total FROM token_snapshots
+                 WHERE network = ?1 AND tick = ?2 AND taken_at >= ?3
+                 ORDER BY taken_at",
+            )?;
+            let samples = statement.query_map(params![network, tick, since as i64], |row| {
+                Ok(HistorySample {
+                    taken_at: row.get::<_, i64>(0)? as u64,
+                    minted: row.get::<_, String>(1)?.parse().unwrap_or(0),
+                    mint_total: row.get::<_, Option<i64>>(2)?.map(|total| total as u64),
+                    holder_total: row.get::<_, Option<i64>>(3)?.map(|total| total as u64),
+                })
+            })?;
+            samples.collect()
+        })
+        .await
+    }
+
+    // Thin out the history: before `downsample_before` only the last snapshot of each token and hour is kept,
+    // before `delete_before` nothing is. Snapshots a whale subscription still compares against are kept.
+    // Their holders go with them. Returns how many snapshots were removed.
+    pub async fn prune_snapshots(&self, downsample_before: u64, delete_before: u64) -> Result<usize, StorageError> {
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let mut removed = tx.execute(
+                "DELETE FROM token_snapshots
+                 WHERE taken_at < ?1
+                   AND id NOT IN (SELECT MAX(id) FROM token_snapshots WHERE taken_at < ?1 GROUP BY network, tick, taken_at / 3600)
+                   AND id NOT IN (SELECT last_snapshot FROM subscriptions)",
+                [downsample_before as i64],
+            )?;
+            removed += tx.execute(
+                "DELETE FROM token_snapshots
+                 WHERE taken_at < ?1
+                   AND id NOT IN (SELECT last_snapshot FROM subscriptions)",
+                [delete_before as i64],
+            )?;
+            tx.commit()?;
+            Ok(removed)
+        })
+        .await
+    }
+
+    // Remember that a user looked at a token; only commands call this, the background samples do not
+    pub async fn record_view(&self, network: &str, tick: &str, viewed_at: u64) -> Result<(), StorageError> {
+        let network = network.to_string();
+        let tick = tick.to_uppercase();
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT INTO token_views (network, tick, last_viewed_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (network, tick) DO UPDATE SET last_viewed_at = MAX(last_viewed_at, excluded.last_viewed_at)",
+                params![network, tick, viewed_at as i64],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Tokens a user looked at since a point in time that were still minting in their latest snapshot
+    pub async fn minting_tokens(&self, since: u64) -> Result<Vec<(String, String)>, StorageError> {
+        self.run(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT latest.network, latest.tick FROM token_snapshots AS latest
+                 JOIN token_views ON token_views.network = latest.network AND token_views.tick = latest.tick
+                 WHERE token_views.last_viewed_at >= ?1
+                   AND latest.taken_at = (SELECT MAX(taken_at) FROM token_snapshots WHERE network = latest.network AND tick = latest.tick)
+                   AND latest.state = 'deployed' AND latest.minted <> latest.max
+                 GROUP BY latest.network, latest.tick",
+            )?;
+            let tokens = statement.query_map([since as i64], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            tokens.collect()
+        })
+        .await
+    }
+
+    // Add a subscription, or reset the announced milestone of an existing one
+    pub async fn subscribe(&self, subscription: Subscription, created_at: u64) -> Result<(), StorageError> {
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT INTO subscriptions (channel_id, guild_id, network, tick, kind, created_at, last_milestone, last_snapshot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (channel_id, network, tick, kind)
+                 DO UPDATE SET last_milestone = excluded.last_milestone, last_snapshot = excluded.last_snapshot",
+                params![
+                    subscription.channel_id as i64,
+                    subscription.guild_id as i64,
+                    subscription.network,
+                    subscription.tick.to_uppercase(),
+                    subscription.kind.name(),
+                    created_at as i64,
+                    subscription.last_milestone,
+                    subscription.last_snapshot,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Remove a subscription, returning whether there was one
+    pub async fn unsubscribe(&self, channel_id: u64, network: &str, tick: &str, kind: SubscriptionKind) -> Result<bool, StorageError> {
+        let network = network.to_string();
+        let tick = tick.to_uppercase();
+        self.run(move |conn| {
+            let removed = conn.execute(
+                "DELETE FROM subscriptions WHERE channel_id = ?1 AND network = ?2 AND tick = ?3 AND kind = ?4",
+                params![channel_id as i64, network, tick, kind.name()],
+            )?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    // Subscriptions of one channel, or of every channel of one kind when no channel is given
+    pub async fn subscriptions(&self, channel_id: Option<u64>, kind: Option<SubscriptionKind>) -> Result<Vec<Subscription>, StorageError> {
+        self.run(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT channel_id, guild_id, network, tick, kind, last_milestone, last_snapshot FROM subscriptions
+                 WHERE (?1 IS NULL OR channel_id = ?1) AND (?2 IS NULL OR kind = ?2)
+                 ORDER BY network, tick, channel_id",
+            )?;
+            let rows = statement.query_map(params![channel_id.map(|id| id as i64), kind.map(|kind| kind.name())], |row| {
+                Ok((
+                    Subscription {
+                        channel_id: row.get::<_, i64>(0)? as u64,
+                        guild_id: row.get::<_, i64>(1)? as u64,
+                        network: row.get(2)?,
+                        tick: row.get(3)?,
+                        kind: SubscriptionKind::Milestones,
+                        last_milestone: row.get(5)?,
+                        last_snapshot: row.get(6)?,
+                    },
+                    row.get::<_, String>(4)?,
+                ))
+            })?;
+
+            let mut subscriptions = Vec::new();
+            for row in rows {
+                let (mut subscription, kind) = row?;
+                // Skip kinds written by a newer version
+                let Some(kind) = SubscriptionKind::from_name(&kind) else {
+                    continue;
+                };
+                subscription.kind = kind;
+                subscriptions.push(subscription);
+            }
+            Ok(subscriptions)
+        })
+        .await
+    }
+
+    // Record the milestone a channel was just told about
+    pub async fn set_last_milestone(&self, subscription: &Subscription, milestone: u32) -> Result<(), StorageError> {
+        let subscription = subscription.clone();
+        self.run(move |conn| {
+            conn.execute(
+                "UPDATE subscriptions SET last_milestone = ?5 WHERE channel_id = ?1 AND network = ?2 AND tick = ?3 AND kind = ?4",
+                params![subscription.channel_id as i64, subscription.network, subscription.tick, subscription.kind.name(), milestone],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Add a deployment feed, or replace the filters of an existing one
+    pub async fn add_deployment_feed(&self, feed: DeploymentFeed, created_at: u64) -> Result<(), StorageError> {
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT INTO deployment_feeds (channel_id, guild_id, network, min_supply, max_pre, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (channel_id, network) DO UPDATE SET min_supply = excluded.min_supply, max_pre = excluded.max_pre",
+                params![
+                    feed.channel_id as i64,
+                    feed.guild_id as i64,
+                    feed.network,
+                    feed.min_supply.to_string(),
+                    feed.max_pre,
+                    created_at as i64,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Remove a deployment feed, returning whether there was one
+    pub async fn remove_deployment_feed(&self, channel_id: u64, network: &str) -> Result<bool, StorageError> {
+        let network = network.to_string();
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let removed = tx.execute(
+                "DELETE FROM deployment_feeds WHERE channel_id = ?1 AND network = ?2",
+                params![channel_id as i64, network],
+            )?;
+            // Without feeds the network is no longer checked, a stale cursor would announce everything
+            // deployed in between once a feed is set up again
+            tx.execute(
+                "DELETE FROM deployment_cursors WHERE network = ?1
+                   AND NOT EXISTS (SELECT 1 FROM deployment_feeds WHERE network = ?1)",
+                params![network],
+            )?;
+            tx.commit()?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    // Deployment feeds of one channel, or of every channel when none is given
+    pub async fn deployment_feeds(&self, channel_id: Option<u64>) -> Result<Vec<DeploymentFeed>, StorageError> {
+        self.run(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT channel_id, guild_id, network, min_supply, max_pre FROM deployment_feeds
+                 WHERE ?1 IS NULL OR channel_id = ?1
+                 ORDER BY network, channel_id",
+            )?;
+            let feeds = statement.query_map(params![channel_id.map(|id| id as i64)], |row| {
+                Ok(DeploymentFeed {
+                    channel_id: row.get::<_, i64>(0)? as u64,
+                    guild_id: row.get::<_, i64>(1)? as u64,
+                    network: row.get(2)?,
+                    min_supply: row.get::<_, String>(3)?.parse().unwrap_or(0),
+                    max_pre: row.get(4)?,
+                })
+            })?;
+            feeds.collect()
+        })
+        .await
+    }
+
+    // Operation score of the newest deployment already announced on a network
+    pub async fn deployment_cursor(&self, network: &str) -> Result<Option<u128>, StorageError> {
+        let network = network.to_string();
+        self.run(move |conn| {
+            let op_score: Option<String> = conn.query_row(
+                "SELECT op_score FROM deployment_cursors WHERE network = ?1",
+                [network],
+                |row| row.get(0),
+            )
+            .optional()?;
+            Ok(op_score.and_then(|score| score.parse().ok()))
+        })
+        .await
+    }
+
+    pub async fn set_deployment_cursor(&self, network: &str, op_score: u128, updated_at: u64) -> Result<(), StorageError> {
+        let network = network.to_string();
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT INTO deployment_cursors (network, op_score, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (network) DO UPDATE SET op_score = excluded.op_score, updated_at = excluded.updated_at",
+                params![network, op_score.to_string(), updated_at as i64],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Add a watched address, or change the label of one already watched
+    pub async fn watch(&self, watch: WatchedAddress, created_at: u64) -> Result<(), StorageError> {
+        self.run(move |conn| {
+            conn.execute(
+                "INSERT INTO watched_addresses (user_id, network, address, label, created_at, balances, failures, next_check)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (user_id, network, address) DO UPDATE SET label = excluded.label",
+                params![
+                    watch.user_id as i64,
+                    watch.network,
+                    watch.address,
+                    watch.label,
+                    created_at as i64,
+                    watch.balances.map(|balances| json!(balances).to_string()),
+                    watch.failures,
+                    watch.next_check as i64,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Stop watching an address on every network, returning whether it was watched
+    pub async fn unwatch(&self, user_id: u64, address: &str) -> Result<bool, StorageError> {
+        let address = address.to_string();
+        self.run(move |conn| {
+            let removed = conn.execute(
+                "DELETE FROM watched_addresses WHERE user_id = ?1 AND address = ?2",
+                params![user_id as i64, address],
+            )?;
+            Ok(removed > 0)
+        })
+        .await
+    }
+
+    // The addresses of one user, or of every user when none is given
+    pub async fn watched_addresses(&self, user_id: Option<u64>) -> Result<Vec<WatchedAddress>, StorageError> {
+        self.run(move |conn| {
+            let mut statement = conn.prepare(
+                "SELECT user_id, network, address, label, balances, failures, next_check FROM watched_addresses
+                 WHERE ?1 IS NULL OR user_id = ?1
+                 ORDER BY user_id, created_at",
+            )?;
+            let watches = statement.query_map(params![user_id.map(|id| id as i64)], |row| {
+                Ok(WatchedAddress {
+                    user_id: row.get::<_, i64>(0)? as u64,
+                    network: row.get(1)?,
+                    address: row.get(2)?,
+                    label: row.get(3)?,
+                    // Balances that no longer parse are treated as not checked yet
+                    balances: row.get::<_, Option<String>>(4)?.and_then(|balances| serde_json::from_str(&balances).ok()),
+                    failures: row.get(5)?,
+                    next_check: row.get::<_, i64>(6)? as u64,
+                })
+            })?;
+            watches.collect()
+        })
+        .await
+    }
+
+    // Save the outcome of a check: the balances seen (None keeps the previous ones) and when to check next
+    pub async fn record_watch_check(&self, watch: &WatchedAddress, balances: Option<Vec<TokenInfo>>, failures: u32, next_check: u64) -> Result<(), StorageError> {
+        let watch = watch.clone();
+        self.run(move |conn| {
+            conn.execute(
+                "UPDATE watched_addresses SET balances = COALESCE(?4, balances), failures = ?5, next_check = ?6
+                 WHERE user_id = ?1 AND network = ?2 AND address = ?3",
+                params![
+                    watch.user_id as i64,
+                    watch.network,
+                    watch.address,
+                    balances.map(|balances| json!(balances).to_string()),
+                    failures,
+                    next_check as i64,
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Record the newest snapshot a channel's whale alerts have covered
+    pub async fn set_last_snapshot(&self, subscription: &Subscription, snapshot_id: i64) -> Result<(), StorageError> {
+        let subscription = subscription.clone();
+        self.run(move |conn| {
+            conn.execute(
+                "UPDATE subscriptions SET last_snapshot = ?5 WHERE channel_id = ?1 AND network = ?2 AND tick = ?3 AND kind = ?4",
+                params![subscription.channel_id as i64, subscription.network, subscription.tick, subscription.kind.name(), snapshot_id],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    // Every cached response saved by the last cache snapshot
+    pub async fn load_cache_entries(&self) -> Result<Vec<(CacheKey, Entry)>, StorageError> {
+        self.run(|conn| {
+            let mut statement = conn.prepare("SELECT network, endpoint, id, value, fetched_at FROM cache_entries")?;
+            let rows = statement.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
+            })?;
+
+            let mut entries = Vec::new();
+            for row in rows {
+                let (network, endpoint, id, value, fetched_at) = row?;
+                // Skip rows written by an older version with an endpoint or value we no longer understand
+                let (Some(endpoint), Ok(value)) = (Endpoint::from_name(&endpoint), serde_json::from_str(&value)) else {
+                    continue;
+                };
+                entries.push((CacheKey { network, endpoint, id }, Entry { value, fetched_at: fetched_at as u64 }));
+            }
+            Ok(entries)
+        })
+        .await
+    }
+
+    // Replace the saved cache snapshot
+    pub async fn save_cache_entries(&self, entries: Vec<(CacheKey, Entry)>) -> Result<(), StorageError> {
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM cache_entries", [])?;
+            {
+                let mut statement = tx.prepare(
+                    "INSERT INTO cache_entries (network, endpoint, id, value, fetched_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for (key, entry) in &entries {
+                    statement.execute(params![
+                        key.network,
+                        key.endpoint.name(),
+                        key.id,
+                        entry.value.to_string(),
+                        entry.fetched_at as i64,
+                    ])?;
+                }
+            }
+            tx.commit()
+        })
+        .await
+    }
+}
+
+const SNAPSHOT_SELECT: &str = "SELECT id, tick, taken_at, state, max, lim, pre, dec, minted, holder_total, mint_total, transfer_total
+    FROM token_snapshots";
+
+fn read_snapshot(row: &rusqlite::Row) -> rusqlite::Result<TokenSnapshot> {
+    let token = ResultStruct {
+        tick: row.get(1)?,
+        state: row.get(3)?,
+        max: row.get(4)?,
+        lim: row.get(5)?,
+        pre: row.get(6)?,
+        dec: row.get(7)?,
+        minted: row.get(8)?,
+        holderTotal: row.get::<_, Option<i64>>(9)?.map(|count| count.to_string()),
+        mintTotal: row.get::<_, Option<i64>>(10)?.map(|count| count.to_string()),
+        transferTotal: row.get::<_, Option<i64>>(11)?.map(|count| count.to_string()),
+        ..Default::default()
+    };
+    Ok(TokenSnapshot { id: row.get(0)?, taken_at: row.get::<_, i64>(2)? as u64, token })
+}
+
+fn with_holders(conn: &Connection, snapshot: Option<TokenSnapshot>) -> rusqlite::Result<Option<TokenSnapshot>> {
+    match snapshot {
+        Some(mut snapshot) => {
+            snapshot.token.holder = Some(read_holders(conn, snapshot.id)?);
+            Ok(Some(snapshot))
+        },
+        None => Ok(None),
+    }
+}
+
+fn read_holders(conn: &Connection, snapshot_id: i64) -> rusqlite::Result<Vec<Holder>> {
+    let mut statement = conn.prepare("SELECT address, amount FROM snapshot_holders WHERE snapshot_id = ?1 ORDER BY rank")?;
+    let holders = statement.query_map([snapshot_id], |row| Ok(Holder { address: row.get(0)?, amount: row.get(1)? }))?;
+    holders.collect()
+}
+
+// Counters arrive as strings; store them as integers so they can be compared in SQL
+fn parse_count(value: &Option<String>) -> Option<i64> {
+    value.as_deref().and_then(|value| value.parse().ok())
+}
+
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+        tx.commit()?;
+        println!("Applied database migration {}", index + 1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 24 * 3600;
+
+    fn minting(tick: &str) -> ResultStruct {
+        ResultStruct {
+            tick: tick.to_string(),
+            state: "deployed".to_string(),
+            max: "1000".to_string(),
+            minted: "10".to_string(),
+            dec: "0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn minting_tokens_follow_views_not_snapshots() {
+        let storage = Storage::open(":memory:").unwrap();
+        let now = 30 * DAY;
+        let since = now - 7 * DAY;
+
+        storage.record_view("mainnet", "OLD", now - 8 * DAY).await.unwrap();
+        storage.record_view("mainnet", "new", now - DAY).await.unwrap();
+        // The recorder keeps sampling both, which must not count as looking at them
+        for tick in ["OLD", "NEW"] {
+            storage.record_snapshot("mainnet", &minting(tick), now).await.unwrap();
+        }
+        assert_eq!(storage.minting_tokens(since).await.unwrap(), vec![("mainnet".to_string(), "NEW".to_string())]);
+
+        // A fresh view brings it back, an older one does not move the last view back
+        storage.record_view("mainnet", "OLD", now).await.unwrap();
+        storage.record_view("mainnet", "OLD", now - 9 * DAY).await.unwrap();
+        assert_eq!(storage.minting_tokens(since).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn finished_tokens_are_not_minting() {
+        let storage = Storage::open(":memory:").unwrap();
+        let mut token = minting("DONE");
+        storage.record_view("mainnet", "DONE", 100).await.unwrap();
+        storage.record_snapshot("mainnet", &token, 100).await.unwrap();
+        assert_eq!(storage.minting_tokens(0).await.unwrap().len(), 1);
+
+        token.minted = token.max.clone();
+        storage.record_snapshot("mainnet", &token, 200).await.unwrap();
+        assert!(storage.minting_tokens(0).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn removing_the_last_feed_forgets_the_cursor() {
+        let storage = Storage::open(":memory:").unwrap();
+        let feed = |channel_id| DeploymentFeed { channel_id, guild_id: 1, network: "mainnet".to_string(), min_supply: 0, max_pre: None };
+        storage.add_deployment_feed(feed(10), 0).await.unwrap();
+        storage.add_deployment_feed(feed(20), 0).await.unwrap();
+        storage.set_deployment_cursor("mainnet", 42, 0).await.unwrap();
+
+        assert!(storage.remove_deployment_feed(10, "mainnet").await.unwrap());
+        assert_eq!(storage.deployment_cursor("mainnet").await.unwrap(), Some(42));
+        assert!(!storage.remove_deployment_feed(10, "mainnet").await.unwrap());
+        assert!(storage.remove_deployment_feed(20, "mainnet").await.unwrap());
+        assert_eq!(storage.deployment_cursor("mainnet").await.unwrap(), None);
+    }
+}
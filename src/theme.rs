@@ -0,0 +1,102 @@
+// src/theme.rs
+use crate::imports::*;
+use crate::hot_reload::{ConfigError, ConfigFile, Reloadable};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::thread_rng;
+use serenity::builder::CreateEmbedFooter;
+use std::sync::Arc;
+
+// Branding shared by every embed the bot sends, reloaded when message_template.json changes
+pub static THEME: Reloadable<Theme> = Reloadable::new();
+
+// A weighted banner image shown at the bottom of the embeds
+#[derive(Debug, Clone, Deserialize)]
+pub struct Banner {
+    pub url: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Author {
+    pub name: String,
+    pub icon_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Footer {
+    pub text: String,
+}
+
+impl Default for Footer {
+    fn default() -> Self {
+        Footer { text: "x.com/coinchimpx".to_string() }
+    }
+}
+
+// Typed contents of message_template.json
+#[derive(Debug, Clone, Deserialize)]
+pub struct Theme {
+    pub color: u32,
+    pub status_color: Option<u32>, // Colour of the token status embeds, falls back to `color`
+    pub background_images: Vec<Banner>,
+    pub author: Author,
+    #[serde(default)]
+    pub footer: Footer,
+}
+
+impl ConfigFile for Theme {
+    const PATH: &'static str = "message_template.json";
+
+    fn parse(content: &str) -> Result<Self, ConfigError> {
+        let theme: Theme = serde_json::from_str(content)?;
+        theme.validate()?;
+        Ok(theme)
+    }
+}
+
+impl Theme {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.background_images.is_empty() {
+            return Err(ConfigError::Invalid("background_images is empty".to_string()));
+        }
+        if self.background_images.iter().all(|banner| banner.weight == 0) {
+            return Err(ConfigError::Invalid("every background image has a weight of 0".to_string()));
+        }
+        if self.author.name.trim().is_empty() {
+            return Err(ConfigError::Invalid("author name is empty".to_string()));
+        }
+        Ok(())
+    }
+
+    // Select a random background image based on weight
+    pub fn random_banner(&self) -> &str {
+        let weights = self.background_images.iter().map(|banner| banner.weight);
+        let dist = WeightedIndex::new(weights).expect("Banner weights are checked on load");
+        let index = dist.sample(&mut thread_rng());
+        &self.background_images[index].url
+    }
+
+    // Embed with the bot branding applied: colour, author, random banner and footer
+    pub fn embed(&self) -> CreateEmbed {
+        CreateEmbed::new()
+            .color(self.color)
+            .image(self.random_banner())
+            .author(CreateEmbedAuthor::new(&self.author.name).icon_url(&self.author.icon_url))
+            .footer(CreateEmbedFooter::new(&self.footer.text))
+    }
+
+    // Branded embed for token status messages
+    pub fn status_embed(&self) -> CreateEmbed {
+        self.embed().color(self.status_color.unwrap_or(self.color))
+    }
+}
+
+// The theme currently in use
+pub fn current() -> Arc<Theme> {
+    THEME.current()
+}